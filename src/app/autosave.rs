@@ -0,0 +1,158 @@
+use super::SaveOptions;
+use backend::world;
+use backend::world::persist;
+use core::clock::{Hourglass, Seconds, Timer};
+use std::io;
+use std::path;
+use std::sync::mpsc;
+use std::thread;
+
+pub type SaveResult = Result<path::PathBuf, persist::Error>;
+
+/// Saves the world at regular intervals. Only taking the snapshot happens during the frame,
+/// writing it to disk and applying the retention policy is left to a worker thread
+pub struct Autosave {
+	/// not set if autosave is disabled
+	hourglass: Option<Hourglass>,
+	pending: Option<mpsc::Receiver<SaveResult>>,
+	/// wall clock time of the last successful save, timed or not
+	last_saved: Option<Seconds>,
+}
+
+impl Autosave {
+	pub fn new(interval: Option<Seconds>, timer: &Timer) -> Self {
+		Autosave {
+			hourglass: interval.map(|interval| Hourglass::new(interval, timer)),
+			pending: None,
+			last_saved: None,
+		}
+	}
+
+	pub fn is_saving(&self) -> bool { self.pending.is_some() }
+
+	pub fn since_last_save<T>(&self, timer: &T) -> Option<Seconds>
+	where T: Timer {
+		self.last_saved.map(|last_saved| timer.seconds() - last_saved)
+	}
+
+	pub fn mark_saved<T>(&mut self, timer: &T)
+	where T: Timer {
+		self.last_saved = Some(timer.seconds());
+	}
+
+	pub fn is_due<T>(&mut self, timer: &T) -> bool
+	where T: Timer {
+		match self.hourglass {
+			Some(ref mut hourglass) => hourglass.flip_if_expired(timer),
+			None => false,
+		}
+	}
+
+	/// Snapshots the world now and writes it in the background
+	pub fn start(&mut self, world: &world::World, containing_dir: &path::Path, save_options: &SaveOptions) {
+		let s_world = persist::Serializer::save_snapshot(world);
+		let file_name = world::World::snapshot_file_name(containing_dir, save_options.format);
+		let containing_dir = containing_dir.to_owned();
+		let retention = save_options.retention.clone();
+		let (sender, receiver) = mpsc::channel();
+		thread::spawn(move || {
+			let result = persist::Serializer::write(&file_name, &s_world).map(|_| file_name);
+			if result.is_ok() {
+				if let Err(e) = retention.apply(&containing_dir) {
+					warn!("Failed to apply the retention policy: {}", e);
+				}
+			}
+			sender.send(result).is_ok();
+		});
+		self.pending = Some(receiver);
+	}
+
+	fn receive<T>(&mut self, timer: &T, result: Result<SaveResult, ()>) -> Option<SaveResult>
+	where T: Timer {
+		self.pending = None;
+		let result = result.unwrap_or_else(|_| {
+			Err(persist::Error::Io(io::Error::new(
+				io::ErrorKind::Other,
+				"the background save has panicked",
+			)))
+		});
+		if result.is_ok() {
+			self.mark_saved(timer);
+		}
+		Some(result)
+	}
+
+	/// The outcome of the background save, once it is over
+	pub fn poll<T>(&mut self, timer: &T) -> Option<SaveResult>
+	where T: Timer {
+		let received = match self.pending {
+			Some(ref receiver) => match receiver.try_recv() {
+				Ok(result) => Ok(result),
+				Err(mpsc::TryRecvError::Empty) => return None,
+				Err(mpsc::TryRecvError::Disconnected) => Err(()),
+			},
+			None => return None,
+		};
+		self.receive(timer, received)
+	}
+
+	/// Waits for the background save to be over, if there is one
+	pub fn wait<T>(&mut self, timer: &T) -> Option<SaveResult>
+	where T: Timer {
+		let received = match self.pending {
+			Some(ref receiver) => receiver.recv().map_err(|_| ()),
+			None => return None,
+		};
+		self.receive(timer, received)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use backend::testing;
+	use core::clock::{seconds, SimulationTimer};
+	use std::fs;
+
+	#[test]
+	fn disabled_autosave_is_never_due() {
+		let mut timer = SimulationTimer::new();
+		let mut autosave = Autosave::new(None, &timer);
+		timer.tick(seconds(1000.));
+		assert!(!autosave.is_due(&timer));
+	}
+
+	#[test]
+	fn autosave_is_due_once_per_interval() {
+		let mut timer = SimulationTimer::new();
+		let mut autosave = Autosave::new(Some(seconds(10.)), &timer);
+		timer.tick(seconds(5.));
+		assert!(!autosave.is_due(&timer));
+		timer.tick(seconds(5.));
+		assert!(autosave.is_due(&timer));
+		assert!(!autosave.is_due(&timer));
+		timer.tick(seconds(10.));
+		assert!(autosave.is_due(&timer));
+	}
+
+	#[test]
+	fn background_saves_can_be_read_back() {
+		let world = testing::populated_world();
+		let dir = testing::temp_path("autosave");
+		let timer = SimulationTimer::new();
+		let mut autosave = Autosave::new(Some(seconds(10.)), &timer);
+		assert!(autosave.poll(&timer).is_none());
+		assert!(autosave.since_last_save(&timer).is_none());
+
+		autosave.start(&world, &dir, &SaveOptions::default());
+		assert!(autosave.is_saving());
+		let saved = autosave.wait(&timer).unwrap();
+		let read = saved.as_ref().map(|file_path| persist::Serializer::peek(file_path));
+		fs::remove_dir_all(&dir).unwrap();
+
+		assert!(!autosave.is_saving());
+		assert!(autosave.since_last_save(&timer).is_some());
+		let contents = read.unwrap().unwrap();
+		assert_eq!(world.agents(world::agent::AgentType::Minion).len(), contents.population);
+	}
+}
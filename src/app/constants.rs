@@ -16,6 +16,9 @@ pub const FRAME_SMOOTH_COUNT: usize = 120;
 pub const FRAME_TIME_TARGET: SecondsValue = 1. / 60.;
 pub const LOG_INTERVAL: SecondsValue = 5.0;
 pub const SAVE_INTERVAL: SecondsValue = 300.0;
+/// simulated time between the states kept in memory to rewind to, and how many are kept
+pub const REWIND_INTERVAL: SecondsValue = 10.0;
+pub const REWIND_CAPACITY: usize = 60;
 pub const DEAD_ZONE: AxisValue = 0.3f32;
 pub const TURN_SPEED: f32 = consts::PI * 200.;
 pub const DEBUG_DRAW_BRAKE_SCALE: f32 = 0.05;
@@ -25,29 +28,73 @@ pub const MAX_FRAME_LENGTH: SecondsValue = (1.0 / 30.0) as SecondsValue;
 pub const THRUST_POWER: f32 = 5000.;
 pub const POWER_BOOST: f32 = 100.;
 pub const DRAG_COEFFICIENT: f32 = 0.000_001;
+pub const FLUID_DENSITY: f32 = 0.5;
+pub const FLUID_DRAG_DEFAULT: f32 = 1.0;
+pub const FLUID_DRAG_PLAYER: f32 = 0.0;
+pub const FLUID_DRAG_TANGENTIAL_RATIO: f32 = 0.05;
+/// share of the move intent applied directly to bodies subject to fluid drag
+pub const FLUID_MOVE_FORCE_RATIO: f32 = 0.25;
+pub const PHYSICS_TIMESTEP: f32 = 1. / 120.;
+pub const PHYSICS_VELOCITY_ITERATIONS: i32 = 8;
+pub const PHYSICS_POSITION_ITERATIONS: i32 = 3;
+pub const PHYSICS_SUBSTEPS: usize = 1;
+pub const PHYSICS_MAX_STEPS: usize = 8;
+pub const PHYSICS_MAX_SPEED: f32 = 500.;
+pub const PHYSICS_MAX_SPIN: f32 = 200.;
 #[allow(unused)]
 pub const COMPASS_SPRING_POWER: f32 = 1000.0;
 pub const JOINT_UPPER_ANGLE: f32 = consts::PI / 6.;
 pub const JOINT_LOWER_ANGLE: f32 = -consts::PI / 6.;
 pub const JOINT_FREQUENCY: f32 = 5.0;
 pub const JOINT_DAMPING_RATIO: f32 = 0.9;
+pub const JOINT_STRENGTH_UNBREAKABLE: f32 = ::std::f32::INFINITY;
+pub const JOINT_STRENGTH_MIN: f32 = 2000.;
+pub const JOINT_STRENGTH_MAX: f32 = 20000.;
 pub const LINEAR_DAMPING_DEFAULT: f32 = 0.8;
 pub const LINEAR_DAMPING_PLAYER: f32 = 2.0;
 pub const ANGULAR_DAMPING: f32 = 0.9;
 pub const PICK_EPS: f32 = 0.001f32;
+pub const SPECIES_SIMILARITY: f32 = 0.9;
+pub const SELECTION_FEED_ENERGY: f32 = 100.;
 pub const DEFAULT_RESOURCE_CHARGE: f32 = 0.8;
 pub const DEFAULT_SPORE_CHARGE: f32 = 0.8;
 pub const DEFAULT_MINION_CHARGE: f32 = 0.3;
+pub const PROP_CHARGE: f32 = 0.2;
 pub const INITIAL_SPAWN_RADIUS_RATIO: f32 = 0.1;
 pub const INITIAL_SPAWN_RADIUS_SLICES: f32 = 19.;
 pub const INITIAL_SPAWN_RADIUS_INCREMENT: f32 = 0.5;
 pub const MATURITY_MINION_DEFAULT: f32 = 0.5;
 pub const MATURITY_DEFAULT: f32 = 1.0;
+pub const DEFAULT_LIFESPAN: SecondsValue = 5.;
+pub const SPORE_DORMANCY_MIN: f32 = 2.;
+pub const SPORE_DORMANCY_MAX: f32 = 10.;
+pub const SPORE_WINDOW_MIN: f32 = 5.;
+pub const SPORE_WINDOW_MAX: f32 = 60.;
+pub const SPORE_DRIFT_MAX: f32 = 2.;
+pub const SPORE_FOOD_TRIGGER_MAX: f32 = 4.;
+pub const SPORE_SENSE_RADIUS: f32 = 10.;
+pub const SPATIAL_INDEX_CELL_SIZE: f32 = 10.;
+pub const RADAR_RANGE_RATIO: f32 = 10.;
+pub const IMMUNITY_MAX: f32 = 1.;
+pub const PATHOGEN_SPONTANEOUS_RATE: f32 = 0.02;
+pub const PATHOGEN_VIRULENCE_MIN: f32 = 0.5;
+pub const PATHOGEN_VIRULENCE_MAX: f32 = 5.;
+pub const PATHOGEN_TRANSMISSIBILITY_MIN: f32 = 0.05;
+pub const PATHOGEN_TRANSMISSIBILITY_MAX: f32 = 0.5;
+pub const PATHOGEN_DURATION_MIN: f32 = 5.;
+pub const PATHOGEN_DURATION_MAX: f32 = 30.;
+pub const PATHOGEN_MUTATION_RATE: f32 = 0.05;
+pub const PATHOGEN_MUTATION_STEP: f32 = 0.1;
+pub const INFECTION_TINT: [f32; 4] = [0.2, 1.0, 0.1, 1.0];
 pub const GROWTH_COST_RATIO: f32 = 0.1;
 pub const SPAWN_COST_THRESHOLD: f32 = 0.95;
 pub const SPAWN_COST_RATIO: f32 = 0.75;
 pub const COLLISION_BASE_COST: f32 = 0.5;
+/// energy lost by a minion for each unit of impulse its segments take in collisions
+pub const COLLISION_IMPULSE_COST: f32 = 0.02;
 pub const WORLD_RADIUS: f32 = 80.;
+pub const WORLD_CIRCLE_SEGMENTS: usize = 64;
+pub const WORLD_WRAP_MARGIN: f32 = 10.;
 pub const DEFAULT_CHARGE_DECAY_TIME: SecondsValue = 0.5;
 pub const MINION_CHARGE_DECAY_TIME: SecondsValue = 0.25;
 pub const PLAYER_CHARGE_DECAY_TIME: SecondsValue = 0.1;
@@ -68,12 +115,16 @@ pub const DENSITY_RESOURCE: f32 = DENSITY_DEFAULT;
 pub const DENSITY_PLAYER: f32 = 1.0;
 pub const DENSITY_MINION: f32 = 0.2;
 pub const DENSITY_SPORE: f32 = 0.5;
+pub const DENSITY_PROP: f32 = 1.0;
 pub const RESTITUTION_DEFAULT: f32 = 0.6;
 pub const RESTITUTION_PLAYER: f32 = 0.1;
+pub const RESTITUTION_PROP: f32 = 0.3;
 pub const FRICTION_DEFAULT: f32 = 0.7;
 pub const FRICTION_PLAYER: f32 = 0.6;
+pub const FRICTION_PROP: f32 = 0.9;
 pub const B2_LINEAR_SLOP: f32 = 0.005;
 pub const DEFAULT_MINION_GENE_POOL_FILE: &str = "minion_gene_pool.csv";
+pub const DEFAULT_SCENARIO_FILE: &str = "scenario.json";
 pub const DEFAULT_MINION_GENE_POOL: &[&str] = &[
 	"AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA",
 	"AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA",
@@ -91,9 +142,11 @@ pub const DEFAULT_RESOURCE_GENE_POOL: &[&str] = &["GyA21QoQ", "M00sWS0M"];
 
 pub const CONFIG_DIR_HOME: &str = ".config/rust-oids";
 pub const CONFIG_DIR_SAVED_STATE: &str = "saved_state";
+pub const CONFIG_DIR_SPECIMENS: &str = "specimens";
 pub const CONFIG_DIR_RESOURCES: &str = "resources";
 pub const DUMP_FILE_PATTERN_CSV: &str = "%Y%m%d_%H%M%S.csv";
-pub const DUMP_FILE_PATTERN_JSON: &str = "%Y%m%d_%H%M%S.json";
+/// the extension depends on the snapshot format
+pub const DUMP_FILE_PATTERN_SNAPSHOT: &str = "%Y%m%d_%H%M%S";
 
 pub const CAPTURE_FOLDER_TIMESTAMP_PATTERN: &str = "%Y%m%d_%H%M%S";
 pub const CAPTURE_FOLDER: &str = "capture";
@@ -32,10 +32,20 @@ const KEY_PRESSED_ONCE_MAP: &[(input::Key, Event)] = &[
 	(F6, SaveGenePoolToFile),
 	(F7, SaveWorldToFile),
 	(F8, RestartFromCheckpoint),
+	(F4, ToggleSaveBrowser),
+	(OpenBracket, RewindBack),
+	(CloseBracket, RewindForward),
+	(F2, BranchFromRewind),
 	(F10, ToggleDebug),
 	(F12, ToggleCapture),
 	(GamepadStart, ToggleDebug),
 	(Z, DeselectAll),
+	(X, SelectSpecies),
+	(Y, SelectLineage),
+	(F, FeedSelection),
+	(C, FollowSelection),
+	(F9, SaveSelectionToFile),
+	(F11, ExportSpecimen),
 	(L, NextLight),
 	(B, NextBackground),
 	(K, PrevLight),
@@ -104,7 +114,31 @@ impl DefaultController {
 			}
 		}
 
+		// dragging with a modifier selects minions instead of moving the camera
 		match input_state.dragging() {
+			input::Dragging::Begin(_, from) if input_state.any_alt_pressed() => {
+				events.push(Event::BeginLasso(world_transform.to_world(from)));
+			}
+			input::Dragging::Dragging(_, _, to) if input_state.any_alt_pressed() => {
+				events.push(Event::LassoTo(world_transform.to_world(to)));
+			}
+			input::Dragging::End(_, _, to, _) if input_state.any_alt_pressed() => {
+				events.push(Event::LassoTo(world_transform.to_world(to)));
+				events.push(Event::EndLasso);
+			}
+			input::Dragging::Begin(_, _) if input_state.any_ctrl_pressed() => {}
+			input::Dragging::Dragging(_, from, to) if input_state.any_ctrl_pressed() => {
+				events.push(Event::DragArea(
+					world_transform.to_world(from),
+					world_transform.to_world(to),
+				));
+			}
+			input::Dragging::End(_, from, to, _) if input_state.any_ctrl_pressed() => {
+				events.push(Event::PickArea(
+					world_transform.to_world(from),
+					world_transform.to_world(to),
+				));
+			}
 			input::Dragging::Begin(_, from) => {
 				let from = world_transform.to_world(from);
 				events.push(Event::BeginDrag(from, from));
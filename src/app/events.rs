@@ -40,6 +40,12 @@ pub enum Event {
 	SaveGenePoolToFile,
 	SaveWorldToFile,
 	RestartFromCheckpoint,
+	ToggleSaveBrowser,
+	SaveBrowserPage(usize),
+	RewindTo(usize),
+	RewindBack,
+	RewindForward,
+	BranchFromRewind,
 	ToggleDebug,
 
 	TogglePause,
@@ -54,6 +60,17 @@ pub enum Event {
 	PickMinion(Position),
 	SelectMinion(usize),
 	DeselectAll,
+	DragArea(Position, Position),
+	PickArea(Position, Position),
+	BeginLasso(Position),
+	LassoTo(Position),
+	EndLasso,
+	SelectSpecies,
+	SelectLineage,
+	FeedSelection,
+	FollowSelection,
+	SaveSelectionToFile,
+	ExportSpecimen,
 
 	BeginDrag(Position, Position),
 	Drag(Position, Position),
@@ -0,0 +1,256 @@
+use app::constants::*;
+use app::main::make_resource_loader;
+use backend::world;
+use backend::world::agent::AgentType;
+use core::geometry::*;
+use serialize::base64::{self, ToBase64};
+use std::collections::HashSet;
+use std::path;
+
+/// How many genomes are listed when comparing snapshots, the rest are only counted
+const MAX_LISTED_GENOMES: usize = 10;
+
+/// Statistics of a saved world, gathered without starting the simulation
+struct Summary {
+	seconds: f64,
+	extent: Rect,
+	regenerations: usize,
+	extinctions: usize,
+	population: Vec<(AgentType, usize)>,
+	gene_pool: usize,
+	/// of every minion, in ascending order
+	energy: Vec<f32>,
+	genomes: HashSet<String>,
+	traits: Vec<(&'static str, Vec<f32>)>,
+}
+
+impl Summary {
+	fn load(config_home: &path::Path, file_path: &path::Path) -> Result<Summary, world::persist::Error> {
+		let res = make_resource_loader(config_home);
+		let mut world = world::World::new(&res, DEFAULT_MINION_GENE_POOL_FILE);
+		world::persist::Serializer::load(file_path, &mut world)?;
+
+		let minions = world.agents(AgentType::Minion);
+		let mut energy: Vec<f32> = minions.values().map(|agent| agent.state.energy()).collect();
+		energy.sort_by(|a, b| a.partial_cmp(b).unwrap_or(::std::cmp::Ordering::Equal));
+		let genomes = minions.values().map(|agent| agent.dna().to_base64(base64::STANDARD)).collect();
+		let traits = {
+			let collect = |f: &Fn(&world::agent::Agent) -> f32| minions.values().map(|agent| f(agent)).collect::<Vec<_>>();
+			vec![
+				("hunger", collect(&|agent| agent.brain().hunger)),
+				("haste", collect(&|agent| agent.brain().haste)),
+				("prudence", collect(&|agent| agent.brain().prudence)),
+				("fear", collect(&|agent| agent.brain().fear)),
+				("rest", collect(&|agent| agent.brain().rest)),
+				("thrust", collect(&|agent| agent.brain().thrust)),
+				("immunity", collect(&|agent| agent.immunity())),
+				("segments", collect(&|agent| agent.segments().len() as f32)),
+			]
+		};
+		Ok(Summary {
+			seconds: world.seconds().get(),
+			extent: world.extent(),
+			regenerations: world.regenerations(),
+			extinctions: world.extinctions(),
+			population: AgentType::all()
+				.iter()
+				.map(|agent_type| (*agent_type, world.agents(*agent_type).len()))
+				.collect(),
+			gene_pool: world.minion_gene_pool().len(),
+			energy,
+			genomes,
+			traits,
+		})
+	}
+
+	fn population_of(&self, agent_type: AgentType) -> usize {
+		self.population.iter().find(|&&(t, _)| t == agent_type).map_or(0, |&(_, n)| n)
+	}
+}
+
+/// Value at fraction `q` of a sorted list
+fn quantile(sorted: &[f32], q: f32) -> f32 {
+	if sorted.is_empty() {
+		0.
+	} else {
+		sorted[((sorted.len() - 1) as f32 * q).round() as usize]
+	}
+}
+
+fn mean_and_deviation(values: &[f32]) -> (f32, f32) {
+	if values.is_empty() {
+		return (0., 0.);
+	}
+	let n = values.len() as f32;
+	let mean = values.iter().sum::<f32>() / n;
+	let variance = values.iter().map(|v| (v - mean) * (v - mean)).sum::<f32>() / n;
+	(mean, variance.sqrt())
+}
+
+fn print_summary(file_path: &path::Path, summary: &Summary) {
+	println!("{}", file_path.display());
+	println!("  simulated time: {:.1}s", summary.seconds);
+	println!(
+		"  extent: ({:.1}, {:.1}) - ({:.1}, {:.1})",
+		summary.extent.min.x, summary.extent.min.y, summary.extent.max.x, summary.extent.max.y
+	);
+	println!("  regenerations: {} ({} extinctions)", summary.regenerations, summary.extinctions);
+	println!("  gene pool: {} genomes", summary.gene_pool);
+	println!("  population:");
+	for &(agent_type, count) in &summary.population {
+		println!("    {:<16}{:>8}", agent_type.to_string(), count);
+	}
+	let energy = &summary.energy;
+	println!(
+		"  minion energy: min {:.1}, 25% {:.1}, median {:.1}, 75% {:.1}, max {:.1}",
+		quantile(energy, 0.),
+		quantile(energy, 0.25),
+		quantile(energy, 0.5),
+		quantile(energy, 0.75),
+		quantile(energy, 1.)
+	);
+}
+
+fn print_genomes(sign: &str, genomes: &[&String]) {
+	for genome in genomes.iter().take(MAX_LISTED_GENOMES) {
+		println!("    {} {}", sign, genome);
+	}
+	if genomes.len() > MAX_LISTED_GENOMES {
+		println!("    ... and {} more", genomes.len() - MAX_LISTED_GENOMES);
+	}
+}
+
+fn print_diff(before: &Summary, after: &Summary) {
+	println!("population changes:");
+	for agent_type in AgentType::all() {
+		let (from, to) = (before.population_of(*agent_type), after.population_of(*agent_type));
+		if from != to {
+			println!("    {:<16}{:>8} -> {:<8}({:+})", agent_type.to_string(), from, to, to as isize - from as isize);
+		}
+	}
+
+	let mut appeared = after.genomes.difference(&before.genomes).collect::<Vec<_>>();
+	let mut disappeared = before.genomes.difference(&after.genomes).collect::<Vec<_>>();
+	appeared.sort();
+	disappeared.sort();
+	println!("genomes: {} appeared, {} disappeared", appeared.len(), disappeared.len());
+	print_genomes("+", &appeared);
+	print_genomes("-", &disappeared);
+
+	println!("largest trait shifts (mean, in standard deviations of the earlier population):");
+	for shift in trait_shifts(&before.traits, &after.traits) {
+		let relative = match shift.deviations {
+			Some(deviations) => format!("{:+.2} sd", deviations),
+			None if shift.is_empty => "empty population".to_owned(),
+			None => "no earlier spread".to_owned(),
+		};
+		println!(
+			"    {:<16}{:>8.3} -> {:<8.3}({:+.3}, {})",
+			shift.name,
+			shift.mean_from,
+			shift.mean_to,
+			shift.mean_to - shift.mean_from,
+			relative
+		);
+	}
+}
+
+struct TraitShift {
+	name: &'static str,
+	mean_from: f32,
+	mean_to: f32,
+	/// change of the mean against the spread of the earlier population, if there is one
+	deviations: Option<f32>,
+	is_empty: bool,
+}
+
+/// Shifts of the mean of each trait, those measured in standard deviations first, largest first.
+/// Traits without an earlier spread come last, by absolute change
+fn trait_shifts(before: &[(&'static str, Vec<f32>)], after: &[(&'static str, Vec<f32>)]) -> Vec<TraitShift> {
+	let mut shifts = before
+		.iter()
+		.zip(after.iter())
+		.map(|(&(name, ref from), &(_, ref to))| {
+			let (mean_from, deviation) = mean_and_deviation(from);
+			let (mean_to, _) = mean_and_deviation(to);
+			let is_empty = from.is_empty() || to.is_empty();
+			TraitShift {
+				name,
+				mean_from,
+				mean_to,
+				deviations: if is_empty || deviation <= 0. { None } else { Some((mean_to - mean_from) / deviation) },
+				is_empty,
+			}
+		}).collect::<Vec<_>>();
+	let magnitude = |shift: &TraitShift| match shift.deviations {
+		Some(deviations) => (1, deviations.abs()),
+		None => (0, (shift.mean_to - shift.mean_from).abs()),
+	};
+	shifts.sort_by(|a, b| {
+		let (a, b) = (magnitude(a), magnitude(b));
+		b.0.cmp(&a.0).then(b.1.partial_cmp(&a.1).unwrap_or(::std::cmp::Ordering::Equal))
+	});
+	shifts
+}
+
+/// Prints the statistics of each snapshot, and compares them if there are exactly two
+pub fn inspect(config_home: &path::Path, file_paths: &[path::PathBuf]) -> Result<(), world::persist::Error> {
+	let mut summaries = Vec::new();
+	for file_path in file_paths {
+		let summary = Summary::load(config_home, file_path)?;
+		print_summary(file_path, &summary);
+		summaries.push(summary);
+	}
+	if let [ref before, ref after] = summaries[..] {
+		print_diff(before, after);
+	}
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn quantiles_pick_the_nearest_rank() {
+		let sorted = [1., 2., 3., 4., 5.];
+		assert_eq!(1., quantile(&sorted, 0.));
+		assert_eq!(3., quantile(&sorted, 0.5));
+		assert_eq!(4., quantile(&sorted, 0.75));
+		assert_eq!(5., quantile(&sorted, 1.));
+		assert_eq!(0., quantile(&[], 0.5));
+	}
+
+	#[test]
+	fn mean_and_deviation_of_a_population() {
+		assert_eq!((5., 2.), mean_and_deviation(&[2., 4., 4., 4., 5., 5., 7., 9.]));
+		assert_eq!((3., 0.), mean_and_deviation(&[3., 3.]));
+		assert_eq!((0., 0.), mean_and_deviation(&[]));
+	}
+
+	#[test]
+	fn largest_shifts_come_first_and_flat_traits_last() {
+		let before = vec![
+			("small", vec![0., 2.]),
+			("flat", vec![1., 1.]),
+			("large", vec![0., 2.]),
+			("gone", vec![1., 3.]),
+			("flatter", vec![1., 1.]),
+		];
+		let after = vec![
+			("small", vec![1., 3.]),
+			("flat", vec![5., 5.]),
+			("large", vec![4., 6.]),
+			("gone", vec![]),
+			("flatter", vec![2., 2.]),
+		];
+		let shifts = trait_shifts(&before, &after);
+		let names: Vec<_> = shifts.iter().map(|shift| shift.name).collect();
+		assert_eq!(vec!["large", "small", "flat", "gone", "flatter"], names);
+		assert_eq!(Some(4.), shifts[0].deviations);
+		assert_eq!(Some(1.), shifts[1].deviations);
+		assert_eq!(None, shifts[2].deviations);
+		assert!(!shifts[2].is_empty);
+		assert!(shifts[3].is_empty);
+	}
+}
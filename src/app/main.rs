@@ -1,25 +1,31 @@
 use frontend::audio::{self, SoundSystem};
 use frontend::gfx_window_glutin;
+use frontend::input;
 use frontend::input::EventMapper;
 use frontend::input::GamepadEventLoop;
 use frontend::render;
 use frontend::render::{formats, Overlay, Renderer};
 use frontend::ui;
+use std::cell::Cell;
 use std::path;
+use std::process;
 
 use conrod;
 
 use core::clock::{seconds, Hourglass, SecondsValue, SystemTimer};
-use core::math::Directional;
 use core::resource::filesystem::ResourceLoader;
 use core::resource::filesystem::ResourceLoaderBuilder;
 use ctrlc;
 use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc;
 use std::sync::Arc;
+use std::thread;
+use std::time;
 
 use app;
 use app::capture::Capture;
 use app::constants::*;
+use backend::world;
 use glutin;
 use glutin::GlContext;
 use winit::{self, KeyboardInput, VirtualKeyCode, WindowEvent};
@@ -40,10 +46,21 @@ pub fn make_resource_loader(config_home: &path::Path) -> ResourceLoader {
 		).build()
 }
 
+/// Requests from the render loop to the simulation thread
+enum Command {
+	Input(input::Event),
+	Interact(app::Event),
+	Resize(u32, u32),
+	DropFile(path::PathBuf),
+	SaveSlot(path::PathBuf, app::SlotAction),
+	Quit,
+}
+
 pub fn main_loop(
 	minion_gene_pool: &str,
 	config_home: path::PathBuf,
-	world_file: Option<path::PathBuf>,
+	world_files: Vec<path::PathBuf>,
+	save_options: app::SaveOptions,
 	fullscreen: Option<usize>,
 	width: Option<u32>,
 	height: Option<u32>,
@@ -85,30 +102,53 @@ pub fn main_loop(
 	let renderer = &mut render::ForwardRenderer::new(&mut factory, &mut encoder, &res, &frame_buffer).unwrap();
 	let mapper = app::WinitEventMapper::new();
 
-	// Create a new game and run it.
-	let mut app = app::App::new(
-		u32::from(w),
-		u32::from(h),
-		VIEW_SCALE_BASE,
-		config_home,
-		&res,
-		minion_gene_pool,
-		world_file,
-	);
-
 	let mut ui = ui::conrod_ui::Ui::new(&res, &mut factory, &frame_buffer, f64::from(window.hidpi_factor()))
 		.expect("Unable to create UI");
 
-	let audio = audio::ThreadedSoundSystem::new(audio_device).expect("Failure in audio initialization");
-	let mut no_audio = ui::NullAlertPlayer::new();
-	let mut audio_alert_player = audio::ThreadedAlertPlayer::new(audio);
-	app.init(app::SystemMode::Interactive);
+	// the app lives in the simulation thread, the render loop only sees the snapshots it publishes
+	let snapshots = Arc::new(app::SnapshotBuffer::default());
+	let (commands, command_inbox) = mpsc::channel();
+	let (failures, failure_inbox) = mpsc::channel();
+	let simulation = {
+		let snapshots = snapshots.clone();
+		let minion_gene_pool = minion_gene_pool.to_owned();
+		thread::Builder::new()
+			.name("simulation".to_owned())
+			.spawn(move || {
+				let result = simulation_loop(
+					&minion_gene_pool,
+					config_home,
+					world_files,
+					save_options,
+					(u32::from(w), u32::from(h)),
+					audio_device,
+					&command_inbox,
+					&snapshots,
+				);
+				// nothing has been published yet, the render loop is still waiting for the first snapshot
+				if let Err(e) = result {
+					failures.send(e).ok();
+				}
+			}).expect("Unable to start the simulation thread")
+	};
+	// the simulation thread may be gone already, in which case there is nobody left to tell
+	let is_disconnected = Cell::new(false);
+	let send = |command: Command| {
+		if commands.send(command).is_err() {
+			is_disconnected.set(true);
+		}
+	};
 
 	'main: loop {
-		gamepad.poll_events(|event| app.on_input_event(&event));
+		let frame = snapshots.latest();
+		// the save browser is shown even when the HUD is not
+		let is_browsing_saves = frame.as_ref().map_or(false, |frame| frame.current().save_slots.is_some());
+		let has_ui_overlay = is_browsing_saves || frame.as_ref().map_or(false, |frame| frame.current().has_ui_overlay);
+
+		gamepad.poll_events(|event| send(Command::Input(event)));
 
 		events_loop.poll_events(|event| {
-			if app.has_ui_overlay() {
+			if has_ui_overlay {
 				if let Some(event) = conrod::backend::winit::convert_event(event.clone(), window.window()) {
 					ui.push_event(event);
 				}
@@ -119,9 +159,10 @@ pub fn main_loop(
 						gfx_window_glutin::update_views(&window, &mut frame_buffer, &mut depth_buffer);
 						renderer.resize_to(&frame_buffer).expect("Unable to resize window");
 						ui.resize_to(&frame_buffer).expect("Unable to resize window");
-						app.on_resize(new_width, new_height);
+						send(Command::Resize(new_width, new_height));
 					}
-					WindowEvent::Closed => app.quit(),
+					WindowEvent::Closed => send(Command::Quit),
+					WindowEvent::DroppedFile(file_path) => send(Command::DropFile(file_path)),
 					WindowEvent::KeyboardInput {
 						input: KeyboardInput {
 							virtual_keycode: Some(VirtualKeyCode::F5),
@@ -129,83 +170,190 @@ pub fn main_loop(
 						},
 						..
 					} => renderer.rebuild().unwrap(),
-					e => if let Some(i) = mapper.translate(&e) {
-						app.on_input_event(&i)
+					e => match mapper.translate(&e) {
+						// keys typed in the save browser are not hotkeys, only F4 closes it; releases still go through
+						Some(input::Event::Key(input::State::Down, key)) if is_browsing_saves && key != input::Key::F4 => {}
+						Some(i) => send(Command::Input(i)),
+						None => {}
 					},
 				}
 			}
 		});
 
-		capture.enable(app.is_capturing());
-
-		if !app.is_running() {
-			capture.stop();
-			app.save_world_to_file();
+		// a simulation that quits publishes its last snapshot before leaving, otherwise it has panicked
+		let has_crashed = || snapshots.latest().map_or(true, |frame| frame.current().is_running);
+		match failure_inbox.try_recv() {
+			Ok(e) => {
+				error!("Unable to start the simulation: {}", e);
+				break 'main;
+			}
+			Err(mpsc::TryRecvError::Disconnected) if has_crashed() => {
+				error!("The simulation has stopped unexpectedly");
+				break 'main;
+			}
+			Err(_) => {}
+		}
+		if is_disconnected.get() && has_crashed() {
+			error!("The simulation is no longer receiving commands");
 			break 'main;
 		}
 
-		let speed_factor = app.speed_factors.get();
-		let frame_update = if capture.enabled() || speed_factor > 5.0 {
-			// forces 60Hz simulation for frame capture and fast forward
-			app.update_with_quantum(Some(FRAME_TIME_TARGET))
-		} else {
-			// update and measure, let the app determine the appropriate frame length
-			app.update()
+		let frame = match frame {
+			Some(frame) => frame,
+			None => {
+				thread::sleep(time::Duration::from_millis(1));
+				continue 'main;
+			}
 		};
+		let snapshot = frame.current();
+
+		capture.enable(snapshot.is_capturing);
+
+		if !snapshot.is_running {
+			capture.stop();
+			break 'main;
+		}
 
-		let camera = render::Camera::ortho(app.camera.position(), app.viewport.scale, app.viewport.ratio);
+		let camera = render::Camera::ortho(frame.camera_position(), snapshot.viewport_scale, snapshot.viewport_ratio);
 
-		let environment = app.environment();
+		let environment = frame.environment();
 
 		renderer.setup_frame(&camera, environment.background_color, &environment.lights);
 		// draw a frame
 		renderer.begin_frame();
 		// draw the scene
-		app.paint(renderer);
+		frame.paint(renderer);
 		// post-render effects and tone mapping
 		renderer.resolve_frame_buffer();
 
-		if app.has_ui_overlay() {
-			let screen = ui::Screen::Main(frame_update);
+		if has_ui_overlay {
+			let screen = match snapshot.save_slots {
+				Some(ref save_slots) => ui::Screen::SaveSlots(save_slots.clone()),
+				None => ui::Screen::Main(snapshot.frame_update.clone()),
+			};
 			renderer.overlay(|_, encoder| {
 				ui.update_and_draw_screen(&screen, encoder);
 			});
 			ui.handle_events();
 
 			for app_event in ui.drain_app_events() {
-				app.interact(app_event)
+				send(Command::Interact(app_event))
+			}
+			for (file_path, action) in ui.drain_slot_actions() {
+				send(Command::SaveSlot(file_path, action))
 			}
 		}
 
+		// push the commands
+		renderer.end_frame(&mut device);
+		capture.screen_grab();
+
+		window.swap_buffers().expect("swap_buffers() failed");
+		renderer.cleanup(&mut device);
+	}
+	if simulation.join().is_err() {
+		error!("The simulation thread has panicked");
+		process::exit(1);
+	}
+}
+
+/// Runs the app at a steady pace, publishing a snapshot after every update.
+/// At high speed factors an update may take longer than a frame, the render loop keeps its own pace regardless
+fn simulation_loop(
+	minion_gene_pool: &str,
+	config_home: path::PathBuf,
+	world_files: Vec<path::PathBuf>,
+	save_options: app::SaveOptions,
+	(width, height): (u32, u32),
+	audio_device: Option<usize>,
+	commands: &mpsc::Receiver<Command>,
+	snapshots: &app::SnapshotBuffer,
+) -> Result<(), world::persist::Error>
+{
+	let res = make_resource_loader(&config_home);
+
+	// Create a new game and run it.
+	let mut app = app::App::new(
+		width,
+		height,
+		VIEW_SCALE_BASE,
+		config_home,
+		&res,
+		minion_gene_pool,
+		world_files,
+	)?;
+	app.set_save_options(save_options);
+
+	let audio = audio::ThreadedSoundSystem::new(audio_device).expect("Failure in audio initialization");
+	let mut no_audio = ui::NullAlertPlayer::new();
+	let mut audio_alert_player = audio::ThreadedAlertPlayer::new(audio);
+	app.init(app::SystemMode::Interactive);
+
+	let frame_length = time::Duration::new(0, (FRAME_TIME_TARGET * 1e9) as u32);
+	loop {
+		let frame_start = time::Instant::now();
+		for command in commands.try_iter() {
+			match command {
+				Command::Input(event) => app.on_input_event(&event),
+				Command::Interact(event) => app.interact(event),
+				Command::Resize(width, height) => app.on_resize(width, height),
+				Command::DropFile(file_path) => app.import_specimen(&file_path),
+				Command::SaveSlot(file_path, action) => app.on_slot_action(&file_path, action),
+				Command::Quit => app.quit(),
+			}
+		}
+
+		let speed_factor = app.speed_factors.get();
+		let frame_update = app.update_with_quantum(Some(FRAME_TIME_TARGET));
+
 		if speed_factor < 10.0 {
 			app.play_alerts(&mut audio_alert_player);
 		} else {
 			app.play_alerts(&mut no_audio);
 		};
 
-		// push the commands
-		renderer.end_frame(&mut device);
-		capture.screen_grab();
+		if !app.is_running() {
+			app.save_world_to_file();
+			snapshots.publish(app.snapshot(frame_update));
+			return Ok(());
+		}
+		app.update_autosave();
+		snapshots.publish(app.snapshot(frame_update));
 
-		window.swap_buffers().expect("swap_buffers() failed");
-		renderer.cleanup(&mut device);
+		let elapsed = frame_start.elapsed();
+		if elapsed < frame_length {
+			thread::sleep(frame_length - elapsed);
+		}
 	}
 }
 
-pub fn main_loop_headless(minion_gene_pool: &str, config_home: path::PathBuf, world_file: Option<path::PathBuf>) {
+pub fn main_loop_headless(
+	minion_gene_pool: &str,
+	config_home: path::PathBuf,
+	world_files: Vec<path::PathBuf>,
+	save_options: app::SaveOptions,
+)
+{
 	const WIDTH: u32 = 1024;
 	const HEIGHT: u32 = 1024;
 	let res = make_resource_loader(&config_home);
 
-	let mut app = app::App::new(
+	let mut app = match app::App::new(
 		WIDTH,
 		HEIGHT,
 		VIEW_SCALE_BASE,
 		config_home,
 		&res,
 		minion_gene_pool,
-		world_file,
-	);
+		world_files,
+	) {
+		Ok(app) => app,
+		Err(e) => {
+			error!("Unable to start the simulation: {}", e);
+			return;
+		}
+	};
+	app.set_save_options(save_options);
 	let mut no_audio = ui::NullAlertPlayer::new();
 	app.init(app::SystemMode::Batch);
 
@@ -218,7 +366,6 @@ pub fn main_loop_headless(minion_gene_pool: &str, config_home: path::PathBuf, wo
 
 	let wall_clock = SystemTimer::new();
 	let mut output_hourglass = Hourglass::new(seconds(LOG_INTERVAL), &wall_clock);
-	let mut save_hourglass = Hourglass::new(seconds(SAVE_INTERVAL), &wall_clock);
 
 	const FRAME_SIMULATION_LENGTH: SecondsValue = FRAME_TIME_TARGET;
 	'main: loop {
@@ -233,18 +380,17 @@ pub fn main_loop_headless(minion_gene_pool: &str, config_home: path::PathBuf, wo
 		}
 		// update and measure
 		let simulation_update = app.simulate(seconds(FRAME_SIMULATION_LENGTH));
-		if save_hourglass.flip_if_expired(&wall_clock) {
-			app.save_world_to_file();
-		}
+		app.update_autosave();
 
 		app.play_alerts(&mut no_audio);
 		if output_hourglass.flip_if_expired(&wall_clock) {
 			info!(
-				"C: {} E: {:.3} FT: {:.2} P: {} X: {}",
+				"C: {} E: {:.3} FT: {:.2} P: {} I: {} X: {}",
 				simulation_update.count,
 				simulation_update.elapsed,
 				simulation_update.dt,
 				simulation_update.population,
+				simulation_update.infected,
 				simulation_update.extinctions
 			)
 		}
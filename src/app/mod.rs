@@ -5,6 +5,7 @@ use backend::obj::*;
 use backend::systems;
 use backend::world;
 use backend::world::agent;
+use backend::world::gen;
 use backend::world::segment;
 use cgmath;
 use cgmath::Matrix4;
@@ -17,22 +18,29 @@ use core::math::Relative;
 use core::math::Smooth;
 use core::resource::ResourceLoader;
 use core::util::Cycle;
+use core::view::ViewTransform;
 use core::view::Viewport;
 use core::view::WorldTransform;
 use dirs;
 use frontend::input;
+use frontend::input::InputRead;
 use frontend::render;
 use frontend::ui;
 use getopts::Options;
 use num;
 use rayon::prelude::*;
-use std::ffi::OsStr;
-use std::fs;
+use std::collections::HashSet;
+use std::mem;
 use std::path;
 
 pub use self::controller::DefaultController;
 pub use self::controller::InputController;
 pub use self::events::Event;
+pub use self::retention::RetentionPolicy;
+pub use self::slots::{SaveSlot, SlotAction, SlotPage, SAVE_SLOTS_PER_PAGE};
+pub use self::snapshot::{Frame, Snapshot, SnapshotBuffer};
+use self::autosave::Autosave;
+use self::rewind::Rewind;
 use self::events::VectorDirection;
 pub use self::winit_event::WinitEventMapper;
 pub use self::winit_event::WinitEventMapper as EventMapper;
@@ -44,12 +52,18 @@ use std::sync::Arc;
 use std::sync::RwLock;
 
 //#[cfg(feature="capture")]
+mod autosave;
 mod capture;
 
 mod controller;
 mod events;
+mod inspect;
 mod main;
 mod paint;
+mod retention;
+mod rewind;
+mod slots;
+mod snapshot;
 mod winit_event;
 
 pub mod constants;
@@ -65,45 +79,136 @@ pub fn run(args: &[OsString]) {
 		"~/.config/rust-oids/saved_state/20180423_234300.json",
 	);
 	opt.optflag("n", "new", "Ignore last snapshot, start from new population");
+	opt.optopt("s", "snapshot_format", "Format of saved snapshots: json, json.gz or bin", "json");
+	opt.optopt(
+		"c",
+		"convert",
+		"Convert a snapshot to the format of the output file, then exit",
+		"~/.config/rust-oids/saved_state/20180423_234300.json",
+	);
+	opt.optopt("o", "output", "Output file of a conversion", "20180423_234300.bin");
+	opt.optopt(
+		"",
+		"autosave",
+		"Save the world every N seconds, 0 to disable",
+		&SAVE_INTERVAL.to_string(),
+	);
+	opt.optopt("", "keep_last", "Keep only the N most recent snapshots", "N");
+	opt.optopt("", "keep_hourly", "Also keep the newest snapshot of each of the last N hours", "N");
+	opt.optopt("", "keep_daily", "Also keep the newest snapshot of each of the last N days", "N");
+	opt.optopt("", "max_disk_mb", "Delete the oldest snapshots beyond this total size", "MB");
+	opt.optmulti(
+		"",
+		"inspect",
+		"Print statistics of a snapshot, or compare two snapshots if given twice, then exit",
+		"FILE",
+	);
+	opt.optflag("", "retention_dry_run", "List the snapshots the retention policy would delete, then exit");
 	opt.optopt("w", "width", "Window width", "1024");
 	opt.optopt("h", "height", "Window height", "1024");
 	opt.optopt("a", "audio_device", "Audio device index (portaudio)", "0");
 	match opt.parse(args) {
 		Ok(options) => {
+			if let Some(from_path) = options.opt_str("c") {
+				let to_path = match options.opt_str("o") {
+					Some(to_path) => to_path,
+					None => {
+						eprintln!("Please specify the output file of the conversion with -o");
+						process::exit(1)
+					}
+				};
+				match world::persist::Serializer::convert(path::Path::new(&from_path), path::Path::new(&to_path)) {
+					Ok(()) => info!("Converted {} to {}", from_path, to_path),
+					Err(e) => {
+						eprintln!("Unable to convert {}: {}", from_path, e);
+						process::exit(1)
+					}
+				}
+				return;
+			}
+
+			let format = match options.opt_str("s") {
+				None => world::persist::Format::default(),
+				Some(name) => match world::persist::Format::parse(&name) {
+					Some(format) => format,
+					None => {
+						eprintln!("Unknown snapshot format: {}", name);
+						process::exit(1)
+					}
+				},
+			};
+			// a typo here would silently keep or delete the wrong snapshots
+			let count_option = |name: &str| match options.opt_str(name) {
+				None => None,
+				Some(value) => match value.parse::<u64>() {
+					Ok(count) => Some(count),
+					Err(_) => {
+						eprintln!("Invalid value for --{}: {}", name, value);
+						process::exit(1)
+					}
+				},
+			};
+			let retention = RetentionPolicy {
+				keep_last: count_option("keep_last").map(|n| n as usize),
+				keep_hourly: count_option("keep_hourly").map_or(0, |n| n as usize),
+				keep_daily: count_option("keep_daily").map_or(0, |n| n as usize),
+				max_bytes: count_option("max_disk_mb").map(|mb| mb * 1024 * 1024),
+			};
+			let autosave = options
+				.opt_str("autosave")
+				.and_then(|v| v.parse::<SecondsValue>().ok())
+				.unwrap_or(SAVE_INTERVAL);
+			let save_options = SaveOptions {
+				format,
+				retention,
+				autosave: if autosave > 0. { Some(seconds(autosave)) } else { None },
+			};
+
 			let pool_file_name = options
 				.free
 				.get(1)
 				.map(|n| n.as_str())
 				.unwrap_or(DEFAULT_MINION_GENE_POOL_FILE);
 
-			let mut world_file: Option<path::PathBuf> = options.opt_str("i").map(|s| path::Path::new(&s).to_owned());
+			let mut world_files: Vec<path::PathBuf> =
+				options.opt_str("i").map(|s| path::Path::new(&s).to_owned()).into_iter().collect();
 
 			// we look for the last save in ~/.config/rust-oids/saved_state
 			// but only if -n and -i are not specified
 			let user_home = dirs::home_dir().unwrap_or_else(|| path::PathBuf::from("."));
 			let config_home = user_home.join(CONFIG_DIR_HOME);
-			if !options.opt_present("n") && world_file.is_none() {
-				let mut max_path = None;
-				if let Ok(dir) = fs::read_dir(config_home.join(CONFIG_DIR_SAVED_STATE)) {
-					// get the highest file in lexicographical order
-					// or the first one, if any
-					for entry in dir {
-						let path_name = entry.unwrap().path().to_owned();
-						if path_name.extension().to_owned().and_then(OsStr::to_str) == Some("json")
-							&& max_path.as_ref().map(|m| path_name > *m).unwrap_or(true)
-						{
-							max_path = Some(path_name.clone());
-						}
+			let inspected = options.opt_strs("inspect");
+			if !inspected.is_empty() {
+				let file_paths = inspected.iter().map(path::PathBuf::from).collect::<Vec<_>>();
+				if let Err(e) = inspect::inspect(&config_home, &file_paths) {
+					eprintln!("Unable to inspect snapshot: {}", e);
+					process::exit(1)
+				}
+				return;
+			}
+			if options.opt_present("retention_dry_run") {
+				match save_options.retention.expired(&config_home.join(CONFIG_DIR_SAVED_STATE)) {
+					Ok(expired) => for path in expired {
+						println!("{}", path.display());
+					},
+					Err(e) => {
+						eprintln!("Unable to list snapshots: {}", e);
+						process::exit(1)
 					}
 				}
-				if max_path.is_some() {
-					world_file = max_path;
-					info!("Resuming simulation from snapshot: {:?}", world_file.as_ref().unwrap());
+				return;
+			}
+			if !options.opt_present("n") && world_files.is_empty() {
+				// most recent save first, older ones are there to fall back on if it cannot be loaded.
+				// Snapshots renamed in the save browser are only loaded from there
+				world_files = retention::timestamped(&config_home.join(CONFIG_DIR_SAVED_STATE)).unwrap_or_default();
+				if let Some(world_file) = world_files.first() {
+					info!("Resuming simulation from snapshot: {:?}", world_file);
 				}
 			}
 
 			if options.opt_present("t") {
-				main::main_loop_headless(pool_file_name, config_home, world_file);
+				main::main_loop_headless(pool_file_name, config_home, world_files, save_options);
 			} else {
 				let fullscreen = options.opt_default("f", "0").and_then(|v| v.parse::<usize>().ok());
 				let width = options.opt_default("w", "1024").and_then(|v| v.parse::<u32>().ok());
@@ -113,7 +218,8 @@ pub fn run(args: &[OsString]) {
 				main::main_loop(
 					pool_file_name,
 					config_home,
-					world_file,
+					world_files,
+					save_options,
 					fullscreen,
 					width,
 					height,
@@ -175,6 +281,7 @@ pub struct Systems {
 	game: Arc<RwLock<systems::GameSystem>>,
 	ai: Arc<RwLock<systems::AiSystem>>,
 	alife: Arc<RwLock<systems::AlifeSystem>>,
+	infection: Arc<RwLock<systems::InfectionSystem>>,
 	particle: Arc<RwLock<systems::ParticleSystem>>,
 }
 
@@ -190,12 +297,14 @@ impl Systems {
 				SendSystem::boxed(self.game.clone()),
 				SendSystem::boxed(self.ai.clone()),
 				SendSystem::boxed(self.alife.clone()),
+				SendSystem::boxed(self.infection.clone()),
 			],
 			SystemMode::Batch => vec![
 				SendSystem::boxed(self.physics.clone()),
 				SendSystem::boxed(self.game.clone()),
 				SendSystem::boxed(self.ai.clone()),
 				SendSystem::boxed(self.alife.clone()),
+				SendSystem::boxed(self.infection.clone()),
 			],
 		}
 	}
@@ -238,6 +347,8 @@ impl Systems {
 		}
 	}
 
+	pub fn pick_area(&self, area: Rect) -> Vec<Id> { self.physics.read().unwrap().pick_area(area) }
+
 	fn for_each_read(
 		&mut self,
 		world: &mut world::World,
@@ -261,6 +372,15 @@ bitflags! {
 
 pub type SpeedFactor = f64;
 
+/// How saved snapshots are written and how many of them are kept
+#[derive(Clone, Debug, Default)]
+pub struct SaveOptions {
+	pub format: world::persist::Format,
+	pub retention: RetentionPolicy,
+	/// interval between timed saves, none if disabled
+	pub autosave: Option<Seconds>,
+}
+
 pub struct App {
 	pub viewport: Viewport,
 	pub zoom: math::ExponentialFilter<f32>,
@@ -278,6 +398,8 @@ pub struct App {
 	//
 	camera: math::Inertial<f32>,
 	is_camera_tracking: bool,
+	is_following_selection: bool,
+	selection_outline: Vec<Position>,
 	lights: Cycle<Rgba>,
 	backgrounds: Cycle<Rgba>,
 	speed_factors: Cycle<SpeedFactor>,
@@ -291,7 +413,13 @@ pub struct App {
 	#[allow(unused)]
 	config_home: path::PathBuf,
 	saved_state_dir: path::PathBuf,
+	save_options: SaveOptions,
 	last_saved: Option<path::PathBuf>,
+	autosave: Autosave,
+	/// listed by the save browser, none if it is closed
+	/// shared with the snapshots while the save browser is open
+	save_slots: Option<Arc<SlotPage>>,
+	rewind: Rewind,
 	//
 	debug_flags: DebugFlags,
 	has_ui_overlay: bool,
@@ -309,6 +437,7 @@ pub struct SimulationUpdate {
 	pub count: usize,
 	pub elapsed: Seconds,
 	pub population: usize,
+	pub infected: usize,
 	pub extinctions: usize,
 }
 
@@ -321,6 +450,13 @@ pub struct FrameUpdate {
 	pub elapsed: Seconds,
 	pub duration_smooth: Seconds,
 	pub fps: f32,
+	/// wall clock time since the world was last saved
+	pub since_last_save: Option<Seconds>,
+	pub is_saving: bool,
+	/// states kept in memory to rewind to, and the one being shown
+	pub rewind_len: usize,
+	pub rewind_position: Option<usize>,
+	pub rewind_offset: Option<Seconds>,
 	pub simulation: SimulationUpdate,
 }
 
@@ -332,8 +468,8 @@ impl App {
 		config_home: path::PathBuf,
 		resource_loader: &R,
 		minion_gene_pool: &str,
-		world_file: Option<path::PathBuf>,
-	) -> Self
+		world_files: Vec<path::PathBuf>,
+	) -> Result<Self, world::persist::Error>
 	where
 		R: ResourceLoader<u8>,
 	{
@@ -350,20 +486,38 @@ impl App {
 		}));
 
 		let mut new_world = world::World::new(resource_loader, minion_gene_pool);
-		let last_saved = world_file.map(|world_file| {
-			if world::persist::Serializer::load(&world_file, &mut new_world).is_err() {
-				panic!(format!("Could not load {:?}", &world_file));
+		// the first snapshot that loads wins, it is an error only if none of them does
+		let mut last_saved = None;
+		let mut last_error = None;
+		for world_file in world_files {
+			match world::persist::Serializer::load(&world_file, &mut new_world) {
+				Ok(()) => {
+					if last_error.is_some() {
+						info!("Resumed from {:?} instead", world_file);
+					}
+					last_saved = Some(world_file);
+					break;
+				}
+				Err(e) => {
+					warn!("Could not load {:?}: {}", world_file, e);
+					last_error = Some(e);
+				}
 			}
-			world_file
-		});
+		}
+		if let (None, Some(e)) = (last_saved.as_ref(), last_error) {
+			return Err(e);
+		}
 
-		App {
+		let autosave = Autosave::new(None, &system_timer);
+		Ok(App {
 			viewport: Viewport::rect(w, h, scale),
 			zoom: math::exponential_filter(1., 1., VIEW_ZOOM_DURATION),
 			input_state: input::InputState::default(),
 
 			camera: Self::init_camera(),
 			is_camera_tracking: true,
+			is_following_selection: false,
+			selection_outline: Vec::new(),
 			lights: Self::init_lights(),
 			backgrounds: Self::init_backgrounds(),
 			speed_factors: Self::init_speed_factors(),
@@ -386,12 +540,16 @@ impl App {
 			is_capturing: false,
 			// savegame
 			saved_state_dir: config_home.join(CONFIG_DIR_SAVED_STATE),
+			save_options: SaveOptions::default(),
 			config_home,
 			last_saved,
+			autosave,
+			save_slots: None,
+			rewind: Rewind::new(seconds(REWIND_INTERVAL), REWIND_CAPACITY),
 			// debug
 			debug_flags: DebugFlags::empty(),
 			has_ui_overlay: true,
-		}
+		})
 	}
 
 	fn on_app_event(&mut self, e: Event) {
@@ -453,9 +611,15 @@ impl App {
 			}
 			Event::ToggleDebug => self.debug_flags.toggle(DebugFlags::DEBUG_TARGETS),
 			Event::RestartFromCheckpoint => self.restart_from_checkpoint(),
+			Event::ToggleSaveBrowser => self.toggle_save_browser(),
+			Event::SaveBrowserPage(page) => self.turn_save_browser_page(page),
 
 			Event::AppQuit => self.quit(),
-			Event::TogglePause => self.is_paused = !self.is_paused,
+			Event::TogglePause => self.toggle_pause(),
+			Event::RewindTo(index) => self.rewind_to(Some(index), 0),
+			Event::RewindBack => self.rewind_to(None, -1),
+			Event::RewindForward => self.rewind_to(None, 1),
+			Event::BranchFromRewind => self.branch_from_rewind(),
 			Event::ToggleGui => self.has_ui_overlay = !self.has_ui_overlay,
 			Event::ToggleCapture => self.is_capturing = !self.is_capturing,
 			Event::SaveGenePoolToFile => self.save_gene_pool_to_file(),
@@ -470,9 +634,31 @@ impl App {
 			Event::EndDrag(start, end, vel) => {
 				self.camera.set_relative(start - end);
 				self.camera.velocity(vel);
+				self.selection_outline.clear();
 			}
 			Event::SelectMinion(id) => self.select_minion(id),
 			Event::DeselectAll => self.deselect_all_minions(),
+			Event::DragArea(start, end) => self.selection_outline = Self::area_outline(start, end),
+			Event::PickArea(start, end) => {
+				let outline = Self::area_outline(start, end);
+				self.select_area(&outline);
+				self.selection_outline.clear();
+			}
+			Event::BeginLasso(start) => self.selection_outline = vec![start],
+			Event::LassoTo(point) => self.selection_outline.push(point),
+			Event::EndLasso => {
+				let outline = mem::replace(&mut self.selection_outline, Vec::new());
+				self.select_area(&outline);
+			}
+			Event::SelectSpecies => self.select_species(),
+			Event::SelectLineage => self.select_lineage(),
+			Event::FeedSelection => self.feed_selection(),
+			Event::FollowSelection => {
+				self.is_following_selection = !self.is_following_selection;
+				self.is_camera_tracking = true;
+			}
+			Event::SaveSelectionToFile => self.save_selection_to_file(),
+			Event::ExportSpecimen => self.export_specimens(),
 			Event::NewMinion(pos) => self.new_minion(pos),
 			Event::RandomizeMinion(pos) => self.randomize_minion(pos),
 			Event::PrimaryFire(_, _) => { /* Handled by the gameplay system */ }
@@ -510,6 +696,117 @@ impl App {
 			.for_each(|a| a.state.toggle_selection());
 	}
 
+	fn area_outline(start: Position, end: Position) -> Vec<Position> {
+		vec![start, Position::new(start.x, end.y), end, Position::new(end.x, start.y)]
+	}
+
+	/// Adds to the selection all the minions whose core lies within the outline
+	fn select_area(&mut self, outline: &[Position]) {
+		if outline.len() < 3 {
+			return;
+		}
+		let bounds = outline.iter().skip(1).fold(
+			Rect::new(outline[0].x, outline[0].y, outline[0].x, outline[0].y),
+			|r, p| Rect::new(r.min.x.min(p.x), r.min.y.min(p.y), r.max.x.max(p.x), r.max.y.max(p.y)),
+		);
+		for id in self.systems.pick_area(bounds) {
+			if let Some(agent) = self.world.agent_mut(id) {
+				if polygon_contains(outline, agent.transform().position) {
+					agent.state.select();
+				}
+			}
+		}
+	}
+
+	/// Adds to the selection all the minions closely related to one already selected
+	fn select_species(&mut self) {
+		let selected = self
+			.world
+			.agents(agent::AgentType::Minion)
+			.values()
+			.filter(|a| a.state.selected())
+			.map(|a| a.dna().clone())
+			.collect::<Vec<_>>();
+		for agent in self.world.agents_mut(agent::AgentType::Minion).values_mut() {
+			if selected
+				.iter()
+				.any(|dna| gen::similarity(dna, agent.dna()) >= SPECIES_SIMILARITY)
+			{
+				agent.state.select();
+			}
+		}
+	}
+
+	/// Adds to the selection all the minions descending from the same founder as one already selected
+	fn select_lineage(&mut self) {
+		let selected = self
+			.world
+			.agents(agent::AgentType::Minion)
+			.values()
+			.filter(|a| a.state.selected())
+			.map(|a| a.lineage())
+			.collect::<HashSet<_>>();
+		for agent in self.world.agents_mut(agent::AgentType::Minion).values_mut() {
+			if selected.contains(&agent.lineage()) {
+				agent.state.select();
+			}
+		}
+	}
+
+	fn feed_selection(&mut self) {
+		for agent in self.world.agents_mut(agent::AgentType::Minion).values_mut() {
+			if agent.state.selected() {
+				agent.state.absorb(SELECTION_FEED_ENERGY);
+			}
+		}
+	}
+
+	/// Centre of the selected minions, if any
+	fn selection_centroid(&self) -> Option<Position> {
+		let (sum, count) = self
+			.world
+			.agents(agent::AgentType::Minion)
+			.values()
+			.filter(|a| a.state.selected())
+			.fold((Position::new(0., 0.), 0), |(sum, count), a| {
+				(sum + a.transform().position, count + 1)
+			});
+		if count > 0 {
+			Some(sum / count as f32)
+		} else {
+			None
+		}
+	}
+
+	pub fn save_selection_to_file(&self) {
+		match self.world.dump_selected(&self.saved_state_dir) {
+			Err(_) => error!("Failed to save selected gene pool"),
+			Ok(path) => info!("Saved {:?}", path),
+		}
+	}
+
+	pub fn export_specimens(&self) {
+		match self.world.export_selected(&self.config_home.join(CONFIG_DIR_SPECIMENS)) {
+			Err(e) => error!("Failed to export specimens: {}", e),
+			Ok(paths) => for path in paths {
+				info!("Exported {:?}", path);
+			},
+		}
+	}
+
+	/// Drops a specimen into the world under the mouse cursor
+	pub fn import_specimen(&mut self, file_path: &path::Path) {
+		match world::persist::Serializer::load_specimen(file_path) {
+			Err(e) => error!("Failed to import {:?}: {}", file_path, e),
+			Ok(specimen) => {
+				let view_position = self.viewport.to_view(self.input_state.mouse_position());
+				let position = self.camera.to_world(view_position);
+				let id = self.world.spawn_specimen(&specimen, position);
+				info!("Imported {:?} as {}", file_path, id);
+			}
+		}
+	}
+
 	pub fn save_gene_pool_to_file(&self) {
 		match self.world.dump(&self.saved_state_dir) {
 			Err(_) => error!("Failed to save gene pool"),
@@ -518,39 +815,197 @@ impl App {
 	}
 
 	pub fn save_world_to_file(&mut self) {
-		let result = self.world.serialize(&self.saved_state_dir);
+		// a background save still in progress would race with the retention policy
+		if let Some(result) = self.autosave.wait(&self.wall_clock) {
+			self.on_autosaved(result);
+		}
+		let result = self.world.serialize(&self.saved_state_dir, self.save_options.format);
 		match result {
-			Err(_) => error!("Failed to save world state"),
+			Err(e) => error!("Failed to save world state: {}", e),
 			Ok(path) => {
 				info!("Saved {:?}", path);
 				self.set_last_saved(path);
+				self.autosave.mark_saved(&self.wall_clock);
+				if let Err(e) = self.save_options.retention.apply(&self.saved_state_dir) {
+					warn!("Failed to apply the retention policy: {}", e);
+				}
+			}
+		}
+	}
+
+	/// Starts a background save when the autosave interval is over, and collects the one in progress
+	pub fn update_autosave(&mut self) {
+		if let Some(result) = self.autosave.poll(&self.wall_clock) {
+			self.on_autosaved(result);
+		}
+		if self.autosave.is_due(&self.wall_clock) {
+			if self.autosave.is_saving() {
+				warn!("Skipping autosave, the previous one is still being written");
+			} else {
+				self.autosave.start(&self.world, &self.saved_state_dir, &self.save_options);
+			}
+		}
+	}
+
+	fn on_autosaved(&mut self, result: autosave::SaveResult) {
+		match result {
+			Err(e) => error!("Failed to autosave world state: {}", e),
+			Ok(path) => {
+				info!("Autosaved {:?}", path);
+				self.set_last_saved(path);
 			}
 		}
 	}
 
 	fn set_last_saved(&mut self, name: path::PathBuf) { self.last_saved = Some(name) }
 
+	pub fn set_save_options(&mut self, save_options: SaveOptions) {
+		self.autosave = Autosave::new(save_options.autosave, &self.wall_clock);
+		self.save_options = save_options;
+	}
+
 	pub fn interact(&mut self, e: Event) {
 		self.bus.post(e.into());
 		self.on_app_event(e)
 	}
 
-	pub fn has_ui_overlay(&self) -> bool { self.has_ui_overlay }
-
 	pub fn quit(&mut self) { self.is_running = false; }
 
 	fn restart_from_checkpoint(&mut self) {
+		let last_saved = self.last_saved.clone();
+		if let Err(e) = self.restart_from(last_saved.as_ref().map(|world_file| world_file.as_path())) {
+			error!("Could not load {:?}: {}", last_saved, e);
+		}
+	}
+
+	/// Starts over with a new world if there is no snapshot to load.
+	/// The snapshot is read and verified first, a snapshot that cannot be loaded leaves the world as it is
+	fn restart_from(&mut self, world_file: Option<&path::Path>) -> Result<(), world::persist::Error> {
+		let src = match world_file {
+			Some(world_file) => Some(world::persist::Serializer::read(world_file)?),
+			None => None,
+		};
 		self.systems.clear();
 		self.world.clear();
-		if let Some(ref world_file) = self.last_saved {
-			world::persist::Serializer::load(&world_file, &mut self.world).is_ok();
+		// the states kept in memory belong to the world being replaced
+		self.rewind.clear();
+		if let Some(ref src) = src {
+			world::persist::Serializer::restore_snapshot(src, &mut self.world);
+		}
+		self.bus.post(world::alert::Alert::RestartFromCheckpoint.into());
+		Ok(())
+	}
+
+	/// Unpausing after a rewind continues from the state being shown
+	fn toggle_pause(&mut self) {
+		self.is_paused = !self.is_paused;
+		if !self.is_paused {
+			self.rewind.resume();
+		}
+	}
+
+	/// Shows the state at the given index, or a number of steps away from the one being shown.
+	/// The simulation is paused until it is resumed or branched
+	fn rewind_to(&mut self, index: Option<usize>, delta: isize) {
+		let shown = {
+			let s_world = match index {
+				Some(index) => self.rewind.scrub_to(&self.world, index),
+				None => self.rewind.step(&self.world, delta),
+			};
+			match s_world {
+				Some(s_world) => {
+					self.systems.clear();
+					self.world.clear();
+					world::persist::Serializer::restore_snapshot(s_world, &mut self.world);
+					true
+				}
+				None => false,
+			}
 		};
-		self.bus.post(world::alert::Alert::RestartFromCheckpoint.into())
+		if shown {
+			self.is_paused = true;
+			self.bus.post(world::alert::Alert::RestartFromCheckpoint.into());
+		}
 	}
 
-	pub fn is_running(&self) -> bool { self.is_running }
+	/// Continues from the state being shown, after saving the present it replaces as a snapshot
+	fn branch_from_rewind(&mut self) {
+		if let Some(present) = self.rewind.resume() {
+			let file_name = world::World::snapshot_file_name(&self.saved_state_dir, self.save_options.format);
+			match world::persist::Serializer::write(&file_name, &present) {
+				Ok(()) => info!("Saved the abandoned branch as {:?}", file_name),
+				Err(e) => error!("Failed to save the abandoned branch: {}", e),
+			}
+		}
+		self.is_paused = false;
+	}
+
+	fn toggle_save_browser(&mut self) {
+		self.save_slots = match self.save_slots {
+			Some(_) => None,
+			None => Some(self.list_save_slots(0)),
+		}
+	}
+
+	fn turn_save_browser_page(&mut self, page: usize) {
+		if self.save_slots.is_some() {
+			self.save_slots = Some(self.list_save_slots(page));
+		}
+	}
+
+	fn list_save_slots(&self, page: usize) -> Arc<SlotPage> {
+		Arc::new(slots::list(&self.saved_state_dir, page).unwrap_or_else(|e| {
+			error!("Could not list {:?}: {}", self.saved_state_dir, e);
+			SlotPage {
+				slots: Vec::new(),
+				index: 0,
+				count: 1,
+			}
+		}))
+	}
+
+	/// Requests from the save browser. Loading a snapshot closes it, and F8 reloads that snapshot from then on
+	pub fn on_slot_action(&mut self, file_path: &path::Path, action: SlotAction) {
+		match action {
+			SlotAction::Load => match self.restart_from(Some(file_path)) {
+				Ok(()) => {
+					info!("Loaded {:?}", file_path);
+					self.set_last_saved(file_path.to_owned());
+					self.save_slots = None;
+					return;
+				}
+				Err(e) => error!("Could not load {:?}: {}", file_path, e),
+			},
+			SlotAction::Duplicate => match slots::duplicate(file_path) {
+				Ok(copy_path) => info!("Copied {:?} to {:?}", file_path, copy_path),
+				Err(e) => error!("Could not copy {:?}: {}", file_path, e),
+			},
+			SlotAction::Rename(ref new_name) => match slots::rename(file_path, new_name) {
+				Ok(new_path) => {
+					info!("Renamed {:?} to {:?}", file_path, new_path);
+					if self.last_saved.as_ref().map_or(false, |last_saved| last_saved == file_path) {
+						self.set_last_saved(new_path);
+					}
+				}
+				Err(e) => error!("Could not rename {:?}: {}", file_path, e),
+			},
+			SlotAction::Delete => match slots::delete(file_path) {
+				Ok(()) => {
+					info!("Deleted {:?}", file_path);
+					if self.last_saved.as_ref().map_or(false, |last_saved| last_saved == file_path) {
+						self.last_saved = None;
+					}
+				}
+				Err(e) => error!("Could not delete {:?}: {}", file_path, e),
+			},
+		}
+		let page = self.save_slots.as_ref().map(|save_slots| save_slots.index);
+		if let Some(page) = page {
+			self.save_slots = Some(self.list_save_slots(page));
+		}
+	}
 
-	pub fn is_capturing(&self) -> bool { self.is_capturing }
+	pub fn is_running(&self) -> bool { self.is_running }
 
 	pub fn on_input_event(&mut self, e: &input::Event) { self.input_state.event(e); }
 
@@ -602,6 +1057,7 @@ impl App {
 	fn cleanup_before(&mut self) {
 		self.world.cleanup_before();
 		self.systems.unregister(&self.world.sweep());
+		self.world.reindex();
 	}
 
 	fn init_systems(&mut self, mode: SystemMode) {
@@ -648,15 +1104,15 @@ impl App {
 		}
 	}
 
-	pub fn update(&mut self) -> FrameUpdate { self.update_with_quantum(None) }
-
 	pub fn update_with_quantum(&mut self, quantum_target: Option<f64>) -> FrameUpdate {
 		let frame_time = self.frame_stopwatch.restart(&self.wall_clock);
 		self.frame_elapsed.tick(frame_time);
 
 		let frame_time_smooth = self.frame_smooth.smooth(frame_time);
 
-		let player_follow = if self.is_camera_tracking {
+		let player_follow = if self.is_camera_tracking && self.is_following_selection {
+			self.selection_centroid()
+		} else if self.is_camera_tracking {
 			self.world.get_player_segment().map(|s| s.transform.position)
 		} else {
 			None
@@ -690,6 +1146,7 @@ impl App {
 
 		let simulation_update = self.simulate(dt);
 		self.frame_count += 1;
+		self.rewind.record(&self.world);
 
 		FrameUpdate {
 			timestamp: self.wall_clock.seconds(),
@@ -699,6 +1156,11 @@ impl App {
 			elapsed: self.frame_elapsed.seconds(),
 			duration_smooth: frame_time_smooth,
 			fps: 1. / target_duration as f32,
+			since_last_save: self.autosave.since_last_save(&self.wall_clock),
+			is_saving: self.autosave.is_saving(),
+			rewind_len: self.rewind.len(),
+			rewind_position: self.rewind.position(),
+			rewind_offset: self.rewind.offset(),
 			simulation: simulation_update,
 		}
 	}
@@ -717,6 +1179,12 @@ impl App {
 			count: self.simulations_count,
 			elapsed: self.world.seconds(),
 			population: self.world.agents(agent::AgentType::Minion).len(),
+			infected: self
+				.world
+				.agents(agent::AgentType::Minion)
+				.values()
+				.filter(|a| a.state.is_infected())
+				.count(),
 			extinctions: self.world.extinctions(),
 		}
 	}
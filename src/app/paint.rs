@@ -2,18 +2,19 @@ use super::*;
 use frontend::render;
 use frontend::render::Style;
 use frontend::render::Draw;
+use core::color::Fade;
+use itertools::Itertools;
 
-impl App {
+impl Frame {
 	pub fn environment(&self) -> Environment {
-		let light_color = self.lights.get();
+		let light_color = self.current.light_color;
 
-		let mut emitter_lights = self.world
-			.feeders()
+		let mut emitter_lights = self.current
+			.feeders
 			.iter()
-			.map(|e| {
-				let intensity = e.intensity();
+			.map(|&(ref transform, intensity)| {
 				render::Light::PointLight {
-					position: e.transform().position,
+					position: transform.position,
 					color: [
 						light_color[0] * intensity,
 						light_color[1] * intensity,
@@ -23,9 +24,7 @@ impl App {
 				}
 			})
 			.collect::<Vec<_>>();
-		if let Some(ref segment) = self.world.get_player_segment() {
-			let position = segment.transform.position;
-			let intensity = segment.state.charge();
+		if let Some((position, intensity)) = self.current.player {
 			emitter_lights.push(render::Light::PointLight {
 				position,
 				color: [
@@ -38,16 +37,16 @@ impl App {
 		}
 
 		Environment {
-			background_color: self.backgrounds.get(),
+			background_color: self.current.background_color,
 			lights: emitter_lights.into_boxed_slice(),
 		}
 	}
 
 	fn paint_particles<R>(&self, renderer: &mut R) where R: render::DrawBuffer {
 		let mut batch = render::PrimitiveBuffer::new();
-		for particle in self.world.particles() {
-			let appearance = render::Appearance::new(particle.color(), particle.effect());
-			let transform = Self::from_transform(&particle.transform()) * Matrix4::from_scale(particle.scale());
+		for particle in &self.current.particles {
+			let appearance = render::Appearance::new(particle.color, particle.effect);
+			let transform = App::from_transform(&particle.transform) * Matrix4::from_scale(particle.scale);
 			batch.draw_quad(Some(Style::Particle), transform, 1.0, appearance);
 		}
 		renderer.draw_buffer(batch);
@@ -55,28 +54,41 @@ impl App {
 
 	fn paint_particles_trails<R>(&self, renderer: &mut R) where R: render::DrawBuffer {
 		let mut batch = render::PrimitiveBuffer::new();
-		for particle in self.world.particles() {
+		for particle in &self.current.particles {
 			use cgmath::SquareMatrix;
-			let appearance = render::Appearance::new(particle.color(), particle.effect());
-			batch.draw_lines(None, Matrix4::identity(), particle.trail(), appearance);
+			let appearance = render::Appearance::new(particle.color, particle.effect);
+			batch.draw_lines(None, Matrix4::identity(), &particle.trail, appearance);
 		}
 		renderer.draw_buffer(batch);
 	}
 
 	fn paint_minions<R>(&self, renderer: &mut R) where R: render::DrawBuffer {
-		for (_, swarm) in self.world.swarms().iter() {
+		let topology = &self.current.topology;
+		for agents in self.current.swarms.values() {
 			let mut batch_buffer = render::PrimitiveBuffer::new();
-			for (_, agent) in swarm.agents().iter() {
-				let energy_left = agent.state.energy_ratio();
-				let phase = agent.state.phase();
-				for segment in agent.segments() {
-					let body_transform = Self::from_transform(&segment.transform());
-
-					let mesh = &segment.mesh();
-					let fixture_scale = Matrix4::from_scale(segment.growing_radius());
+			for (&id, agent) in agents {
+				let energy_left = agent.energy_ratio;
+				let phase = agent.phase;
+				// agents close to the edges of a wrapping world are drawn on the opposite side too
+				let mut offsets = vec![Position::new(0., 0.)];
+				offsets.extend(topology.ghost_offsets(agent.position, WORLD_WRAP_MARGIN).iter());
+				for (offset, (index, segment)) in offsets.iter().cartesian_product(agent.segments.iter().enumerate()) {
+					let body_transform = Matrix4::from_translation(cgmath::Vector3::new(offset.x, offset.y, 0.0))
+						* App::from_transform(&self.segment_transform(id, index, segment));
+
+					let mesh = &segment.mesh;
+					let fixture_scale = Matrix4::from_scale(segment.radius);
 					let transform = body_transform * fixture_scale;
 
-					let appearance = render::Appearance::new(segment.color(), [energy_left, phase, 0., 0.]);
+					let color = match agent.infection_severity {
+						Some(severity) => {
+							let color = segment.color;
+							let tint = [INFECTION_TINT[0], INFECTION_TINT[1], INFECTION_TINT[2], color[3]];
+							color.fade(tint, severity * 0.5)
+						}
+						None => segment.color,
+					};
+					let appearance = render::Appearance::new(color, [energy_left, phase, 0., 0.]);
 
 					match mesh.shape {
 						obj::Shape::Ball { .. } => {
@@ -104,49 +116,65 @@ impl App {
 	fn paint_extent<R>(&self, renderer: &mut R)
 		where R: render::Draw {
 		use cgmath::SquareMatrix;
-		let extent = &self.world.extent;
-		let points = &[
-			extent.min,
-			Position::new(extent.min.x, extent.max.y),
-			extent.max,
-			Position::new(extent.max.x, extent.min.y),
-			extent.min,
-		];
+		let extent = self.current.topology.extent();
+		// wrapping worlds have no walls, but we still show where the seams are
+		let mut points = self.current.topology.boundary().map(|b| b.to_vec()).unwrap_or_else(|| {
+			vec![
+				extent.min,
+				Position::new(extent.min.x, extent.max.y),
+				extent.max,
+				Position::new(extent.max.x, extent.min.y),
+			]
+		});
+		if let Some(&first) = points.first() {
+			points.push(first);
+		}
 		renderer.draw_lines(
 			None,
 			Matrix4::identity(),
-			points,
-			render::Appearance::rgba(self.lights.get()),
+			&points,
+			render::Appearance::rgba(self.current.light_color),
 		);
 		renderer.draw_quad(
 			Some(Style::Stage),
 			Matrix4::from_scale(extent.max.x - extent.min.x),
 			1.,
-			render::Appearance::new(self.backgrounds.get(), self.world.phase()),
+			render::Appearance::new(self.current.background_color, self.current.phase),
 		);
 	}
 
+	fn paint_selection_outline<R>(&self, renderer: &mut R)
+		where R: render::Draw {
+		use cgmath::SquareMatrix;
+		let outline = &self.current.selection_outline;
+		if outline.len() > 1 {
+			let mut points = outline.clone();
+			points.push(outline[0]);
+			renderer.draw_lines(None, Matrix4::identity(), &points, render::Appearance::rgba(COLOR_WHITE));
+		}
+	}
+
 	fn paint_feeders<R>(&self, renderer: &mut R) where R: render::DrawBuffer {
 		let mut batch_buffer = render::PrimitiveBuffer::new();
-		for e in self.world.feeders() {
-			let transform = Self::from_transform(&e.transform());
-			batch_buffer.draw_ball(None, transform, render::Appearance::rgba(self.lights.get()));
+		for &(ref feeder_transform, _) in &self.current.feeders {
+			let transform = App::from_transform(feeder_transform);
+			batch_buffer.draw_ball(None, transform, render::Appearance::rgba(self.current.light_color));
 		}
 		renderer.draw_buffer(batch_buffer)
 	}
 
 	fn paint_hud<R>(&self, renderer: &mut R)
 		where R: render::DrawBuffer {
-		if self.debug_flags.contains(DebugFlags::DEBUG_TARGETS) {
+		if self.current.debug_flags.contains(DebugFlags::DEBUG_TARGETS) {
 			let mut batch_buffer = render::PrimitiveBuffer::new();
 			use cgmath::*;
-			for (_, agent) in self.world.agents(world::agent::AgentType::Minion).iter() {
-				if agent.state.selected() {
-					let sensor = agent.first_segment(segment::Flags::HEAD).unwrap();
-					let p0 = sensor.transform.position;
-					let a0 = sensor.transform.angle;
-					let radar_range = sensor.growing_radius() * 10.;
-					let p1 = agent.state.target_position();
+			let minions = self.current.swarms.get(&world::agent::AgentType::Minion);
+			for agent in minions.into_iter().flat_map(|agents| agents.values()) {
+				if let Some(ref trace) = agent.trace {
+					let p0 = trace.head.position;
+					let a0 = trace.head.angle;
+					let radar_range = trace.head_radius * 10.;
+					let p1 = p0 + self.current.topology.delta(p0, trace.target_position);
 					batch_buffer.draw_lines(
 						Some(Style::DebugLines),
 						Matrix4::identity(),
@@ -176,14 +204,12 @@ impl App {
 						render::Appearance::rgba([0., 1., 0., 1.]),
 					);
 
-					let trajectory = agent.state.trajectory();
-					let appearance = render::Appearance::new(sensor.color(), [2.0, 1.0, 0., 0.]);
-					batch_buffer.draw_lines(Some(Style::DebugLines), Matrix4::identity(), &trajectory, appearance);
+					let appearance = render::Appearance::new(trace.head_color, [2.0, 1.0, 0., 0.]);
+					batch_buffer.draw_lines(Some(Style::DebugLines), Matrix4::identity(), &trace.trajectory, appearance);
 
-					for segment in agent.segments().iter() {
-						match segment.state.intent {
+					for &(p0, ref intent) in &trace.intents {
+						match *intent {
 							segment::Intent::Brake(v) => {
-								let p0 = segment.transform.position;
 								let p1 = p0 + v * DEBUG_DRAW_BRAKE_SCALE;
 								batch_buffer.draw_lines(
 									Some(Style::DebugLines),
@@ -193,7 +219,6 @@ impl App {
 								);
 							}
 							segment::Intent::Move(v) => {
-								let p0 = segment.transform.position;
 								let p1 = p0 + v * DEBUG_DRAW_MOVE_SCALE;
 								batch_buffer.draw_lines(
 									Some(Style::DebugLines),
@@ -218,6 +243,7 @@ impl App {
 		self.paint_particles(renderer);
 		self.paint_particles_trails(renderer);
 		self.paint_extent(renderer);
+		self.paint_selection_outline(renderer);
 		self.paint_hud(renderer);
 	}
 }
\ No newline at end of file
@@ -0,0 +1,244 @@
+use app::constants::*;
+use backend::world::persist;
+use chrono::NaiveDateTime;
+use std::collections::HashSet;
+use std::ffi::OsStr;
+use std::fs;
+use std::io;
+use std::path;
+
+/// Which snapshots to keep in the saved state folder, the others are deleted after every save.
+/// Only files named after the save timestamp are considered, anything renamed by hand is left alone
+#[derive(Clone, Debug, Default)]
+pub struct RetentionPolicy {
+	/// the most recent snapshots, all of them if not set
+	pub keep_last: Option<usize>,
+	/// the newest snapshot of each of the last hours that have one
+	pub keep_hourly: usize,
+	/// the newest snapshot of each of the last days that have one
+	pub keep_daily: usize,
+	/// total size of the snapshots, oldest ones go first. The newest snapshot is always kept
+	pub max_bytes: Option<u64>,
+}
+
+struct SavedSnapshot {
+	path: path::PathBuf,
+	taken: NaiveDateTime,
+	size: u64,
+}
+
+fn saved_snapshots(containing_dir: &path::Path) -> io::Result<Vec<SavedSnapshot>> {
+	let mut snapshots = Vec::new();
+	for entry in fs::read_dir(containing_dir)? {
+		let entry = entry?;
+		let path = entry.path();
+		let format = match persist::Format::from_path(&path) {
+			Some(format) => format,
+			None => continue,
+		};
+		let taken = path
+			.file_name()
+			.and_then(OsStr::to_str)
+			.map(|name| &name[..name.len() - format.extension().len()])
+			.and_then(|stem| NaiveDateTime::parse_from_str(stem, DUMP_FILE_PATTERN_SNAPSHOT).ok());
+		if let Some(taken) = taken {
+			snapshots.push(SavedSnapshot {
+				size: entry.metadata()?.len(),
+				path,
+				taken,
+			});
+		}
+	}
+	// newest first
+	snapshots.sort_by(|a, b| b.taken.cmp(&a.taken).then_with(|| b.path.cmp(&a.path)));
+	Ok(snapshots)
+}
+
+/// Snapshots named after their save timestamp, newest first. Files renamed by hand are not included
+pub fn timestamped(containing_dir: &path::Path) -> io::Result<Vec<path::PathBuf>> {
+	Ok(saved_snapshots(containing_dir)?.into_iter().map(|snapshot| snapshot.path).collect())
+}
+
+impl RetentionPolicy {
+	pub fn is_active(&self) -> bool { self.keep_last.is_some() || self.max_bytes.is_some() }
+
+	/// Snapshots the policy would delete, oldest first
+	pub fn expired(&self, containing_dir: &path::Path) -> io::Result<Vec<path::PathBuf>> {
+		if !self.is_active() {
+			return Ok(Vec::new());
+		}
+		let snapshots = saved_snapshots(containing_dir)?;
+		let mut hours = HashSet::new();
+		let mut days = HashSet::new();
+		let mut total_bytes = 0u64;
+		let mut expired = Vec::new();
+		for (i, snapshot) in snapshots.into_iter().enumerate() {
+			let hour = snapshot.taken.format("%Y%m%d%H").to_string();
+			let day = snapshot.taken.format("%Y%m%d").to_string();
+			let is_recent = self.keep_last.map_or(true, |n| i < n);
+			let is_hourly = !hours.contains(&hour) && hours.len() < self.keep_hourly;
+			let is_daily = !days.contains(&day) && days.len() < self.keep_daily;
+			if is_hourly {
+				hours.insert(hour);
+			}
+			if is_daily {
+				days.insert(day);
+			}
+			let fits = self.max_bytes.map_or(true, |max| total_bytes + snapshot.size <= max);
+			if i == 0 || ((is_recent || is_hourly || is_daily) && fits) {
+				total_bytes += snapshot.size;
+			} else {
+				expired.push(snapshot.path);
+			}
+		}
+		expired.reverse();
+		Ok(expired)
+	}
+
+	pub fn apply(&self, containing_dir: &path::Path) -> io::Result<()> {
+		for path in self.expired(containing_dir)? {
+			fs::remove_file(&path)?;
+			info!("Deleted {:?}", path);
+		}
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use backend::testing;
+
+	/// Snapshots named after the given timestamps, of the given size
+	fn snapshot_dir(name: &str, stamps: &[&str], size: usize) -> path::PathBuf {
+		let dir = testing::temp_dir(name);
+		for stamp in stamps {
+			fs::write(dir.join(format!("{}.json", stamp)), vec![b' '; size]).unwrap();
+		}
+		dir
+	}
+
+	fn expired_stamps(policy: &RetentionPolicy, dir: &path::Path) -> Vec<String> {
+		let expired = policy.expired(dir);
+		fs::remove_dir_all(dir).unwrap();
+		expired
+			.unwrap()
+			.iter()
+			.map(|path| path.file_stem().and_then(OsStr::to_str).unwrap().to_owned())
+			.collect()
+	}
+
+	const STAMPS: &[&str] = &[
+		"20180101_100000",
+		"20180101_105000",
+		"20180101_110000",
+		"20180101_115000",
+		"20180101_120000",
+	];
+
+	#[test]
+	fn nothing_expires_without_a_policy() {
+		let dir = snapshot_dir("retention_none", STAMPS, 1);
+		assert!(expired_stamps(&RetentionPolicy::default(), &dir).is_empty());
+	}
+
+	#[test]
+	fn the_last_snapshots_are_kept() {
+		let dir = snapshot_dir("retention_last", STAMPS, 1);
+		let policy = RetentionPolicy {
+			keep_last: Some(3),
+			..Default::default()
+		};
+		assert_eq!(vec!["20180101_100000", "20180101_105000"], expired_stamps(&policy, &dir));
+	}
+
+	#[test]
+	fn the_newest_snapshot_of_each_hour_is_kept() {
+		let dir = snapshot_dir("retention_hourly", STAMPS, 1);
+		let policy = RetentionPolicy {
+			keep_last: Some(1),
+			keep_hourly: 2,
+			..Default::default()
+		};
+		assert_eq!(
+			vec!["20180101_100000", "20180101_105000", "20180101_110000"],
+			expired_stamps(&policy, &dir)
+		);
+	}
+
+	#[test]
+	fn the_newest_snapshot_of_each_day_is_kept() {
+		let dir = snapshot_dir(
+			"retention_daily",
+			&["20180101_100000", "20180101_200000", "20180102_100000", "20180103_090000", "20180103_100000"],
+			1,
+		);
+		let policy = RetentionPolicy {
+			keep_last: Some(1),
+			keep_daily: 2,
+			..Default::default()
+		};
+		assert_eq!(
+			vec!["20180101_100000", "20180101_200000", "20180103_090000"],
+			expired_stamps(&policy, &dir)
+		);
+	}
+
+	#[test]
+	fn the_oldest_snapshots_go_first_when_over_size() {
+		let dir = snapshot_dir("retention_size", STAMPS, 100);
+		let policy = RetentionPolicy {
+			max_bytes: Some(250),
+			..Default::default()
+		};
+		assert_eq!(
+			vec!["20180101_100000", "20180101_105000", "20180101_110000"],
+			expired_stamps(&policy, &dir)
+		);
+	}
+
+	#[test]
+	fn the_newest_snapshot_is_kept_whatever_its_size() {
+		let dir = snapshot_dir("retention_newest", STAMPS, 100);
+		let policy = RetentionPolicy {
+			max_bytes: Some(10),
+			..Default::default()
+		};
+		assert_eq!(STAMPS.len() - 1, expired_stamps(&policy, &dir).len());
+	}
+
+	#[test]
+	fn apply_leaves_renamed_files_alone() {
+		let dir = snapshot_dir("retention_apply", STAMPS, 1);
+		fs::write(dir.join("keeper.json"), b"{}").unwrap();
+		fs::write(dir.join("notes.txt"), b"").unwrap();
+		let policy = RetentionPolicy {
+			keep_last: Some(1),
+			..Default::default()
+		};
+		policy.apply(&dir).unwrap();
+		let mut left: Vec<_> = fs::read_dir(&dir)
+			.unwrap()
+			.map(|entry| entry.unwrap().file_name().into_string().unwrap())
+			.collect();
+		fs::remove_dir_all(&dir).unwrap();
+		left.sort();
+		assert_eq!(vec!["20180101_120000.json", "keeper.json", "notes.txt"], left);
+	}
+
+	#[test]
+	fn timestamped_snapshots_come_newest_first() {
+		let dir = snapshot_dir("retention_timestamped", STAMPS, 1);
+		fs::write(dir.join("keeper.json"), b"{}").unwrap();
+		fs::write(dir.join(format!("{}_copy.json", STAMPS[0])), b"{}").unwrap();
+		let names: Vec<_> = timestamped(&dir)
+			.unwrap()
+			.into_iter()
+			.map(|path| path.file_name().unwrap().to_str().unwrap().to_owned())
+			.collect();
+		fs::remove_dir_all(&dir).unwrap();
+		let mut expected: Vec<_> = STAMPS.iter().map(|stamp| format!("{}.json", stamp)).collect();
+		expected.sort_by(|a, b| b.cmp(a));
+		assert_eq!(expected, names);
+	}
+}
@@ -0,0 +1,191 @@
+use backend::world;
+use backend::world::persist;
+use core::clock::Seconds;
+use std::collections::VecDeque;
+
+/// Recent states of the world kept in memory, oldest first, to go back to an earlier moment.
+/// While scrubbing, the state before the rewind is kept as the last one, so that it can be returned to
+pub struct Rewind {
+	states: VecDeque<(Seconds, persist::World)>,
+	capacity: usize,
+	interval: Seconds,
+	/// the state being shown, none when the simulation follows the present
+	position: Option<usize>,
+}
+
+impl Rewind {
+	pub fn new(interval: Seconds, capacity: usize) -> Self {
+		Rewind {
+			states: VecDeque::with_capacity(capacity + 1),
+			capacity,
+			interval,
+			position: None,
+		}
+	}
+
+	pub fn len(&self) -> usize { self.states.len() }
+
+	pub fn position(&self) -> Option<usize> { self.position }
+
+	/// Simulated time between the state being shown and the present
+	pub fn offset(&self) -> Option<Seconds> {
+		match (self.position.and_then(|i| self.states.get(i)), self.states.back()) {
+			(Some(&(shown, _)), Some(&(present, _))) => Some(shown - present),
+			_ => None,
+		}
+	}
+
+	fn push_present(&mut self, world: &world::World) {
+		self.states.push_back((world.seconds(), persist::Serializer::save_snapshot(world)));
+	}
+
+	fn push(&mut self, world: &world::World) {
+		self.push_present(world);
+		while self.states.len() > self.capacity {
+			self.states.pop_front();
+		}
+	}
+
+	/// Takes a snapshot whenever the interval has passed in simulated time. Nothing is recorded while scrubbing
+	pub fn record(&mut self, world: &world::World) {
+		if self.position.is_some() {
+			return;
+		}
+		let is_due = match self.states.back() {
+			Some(&(last, _)) => world.seconds() - last >= self.interval,
+			None => true,
+		};
+		if is_due {
+			self.push(world);
+		}
+	}
+
+	/// The state to show at the given index, if it is not the one being shown already
+	pub fn scrub_to(&mut self, world: &world::World, index: usize) -> Option<&persist::World> {
+		if self.position.is_none() {
+			// on top of the capacity, so that no recorded state is lost by rewinding
+			self.push_present(world);
+		}
+		let index = index.min(self.states.len() - 1);
+		if self.position == Some(index) {
+			return None;
+		}
+		self.position = Some(index);
+		self.states.get(index).map(|&(_, ref s_world)| s_world)
+	}
+
+	/// Moves away from the state being shown. There is nothing after the present, so stepping forward is
+	/// ignored unless rewound
+	pub fn step(&mut self, world: &world::World, delta: isize) -> Option<&persist::World> {
+		let current = match self.position {
+			Some(position) => position,
+			None if delta < 0 => self.states.len(),
+			None => return None,
+		};
+		let index = (current as isize + delta).max(0) as usize;
+		self.scrub_to(world, index)
+	}
+
+	/// Continues from the state being shown, the later ones are forgotten.
+	/// The present as it was before the rewind is returned, to be saved as a branch
+	pub fn resume(&mut self) -> Option<persist::World> {
+		let position = self.position.take()?;
+		if position + 1 == self.states.len() {
+			// back to the present, nothing to branch from
+			return None;
+		}
+		let present = self.states.pop_back().map(|(_, s_world)| s_world);
+		self.states.truncate(position + 1);
+		present
+	}
+
+	pub fn clear(&mut self) {
+		self.states.clear();
+		self.position = None;
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use backend::testing::new_world;
+	use core::clock::seconds;
+
+	const INTERVAL: f64 = 10.;
+	const CAPACITY: usize = 5;
+
+	/// Runs the clock for a number of intervals, recording at every one
+	fn record_for(rewind: &mut Rewind, world: &mut world::World, intervals: usize) {
+		for _ in 0..intervals {
+			rewind.record(world);
+			world.tick(seconds(INTERVAL));
+		}
+	}
+
+	fn shown_seconds(rewind: &Rewind) -> Option<f64> {
+		rewind.position().map(|position| rewind.states[position].0.get())
+	}
+
+	#[test]
+	fn only_the_most_recent_states_are_kept() {
+		let mut world = new_world();
+		let mut rewind = Rewind::new(seconds(INTERVAL), CAPACITY);
+		record_for(&mut rewind, &mut world, CAPACITY * 2);
+		assert_eq!(CAPACITY, rewind.len());
+		assert!(rewind.scrub_to(&world, 0).is_some());
+		assert_eq!(CAPACITY + 1, rewind.len());
+		assert_eq!(Some(INTERVAL * CAPACITY as f64), shown_seconds(&rewind));
+	}
+
+	#[test]
+	fn states_are_recorded_once_per_interval() {
+		let mut world = new_world();
+		let mut rewind = Rewind::new(seconds(INTERVAL), CAPACITY);
+		for _ in 0..10 {
+			rewind.record(&world);
+			world.tick(seconds(INTERVAL / 4.));
+		}
+		assert_eq!(3, rewind.len());
+	}
+
+	#[test]
+	fn stepping_forward_from_the_present_does_nothing() {
+		let mut world = new_world();
+		let mut rewind = Rewind::new(seconds(INTERVAL), CAPACITY);
+		record_for(&mut rewind, &mut world, 3);
+		assert!(rewind.step(&world, 1).is_none());
+		assert_eq!(None, rewind.position());
+		assert_eq!(3, rewind.len());
+	}
+
+	#[test]
+	fn stepping_back_and_forth_ends_at_the_present() {
+		let mut world = new_world();
+		let mut rewind = Rewind::new(seconds(INTERVAL), CAPACITY);
+		record_for(&mut rewind, &mut world, 3);
+		let present = world.seconds().get();
+		assert!(rewind.step(&world, -1).is_some());
+		assert_eq!(Some(INTERVAL * 2.), shown_seconds(&rewind));
+		// the present is kept as the last state while rewound
+		assert_eq!(4, rewind.len());
+		assert!(rewind.step(&world, -1).is_some());
+		assert_eq!(Some(INTERVAL), shown_seconds(&rewind));
+		assert!(rewind.step(&world, 5).is_some());
+		assert_eq!(Some(present), shown_seconds(&rewind));
+		assert!(rewind.resume().is_none());
+		assert_eq!(None, rewind.position());
+	}
+
+	#[test]
+	fn resuming_forgets_the_later_states_and_returns_the_present() {
+		let mut world = new_world();
+		let mut rewind = Rewind::new(seconds(INTERVAL), CAPACITY);
+		record_for(&mut rewind, &mut world, 4);
+		let present = world.seconds().get();
+		assert!(rewind.scrub_to(&world, 1).is_some());
+		assert_eq!(Some(present), rewind.states.back().map(|&(seconds, _)| seconds.get()));
+		assert!(rewind.resume().is_some());
+		assert_eq!(2, rewind.len());
+		assert_eq!(None, rewind.position());
+	}
+}
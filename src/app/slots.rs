@@ -0,0 +1,205 @@
+use backend::world::persist;
+use chrono::DateTime;
+use chrono::Local;
+use std::ffi::OsStr;
+use std::fs;
+use std::io;
+use std::path;
+
+/// How many snapshots the save browser shows at a time, newest first
+pub const SAVE_SLOTS_PER_PAGE: usize = 12;
+
+/// A snapshot in the saved state folder, as listed by the save browser
+#[derive(Clone, Debug)]
+pub struct SaveSlot {
+	pub path: path::PathBuf,
+	pub name: String,
+	pub modified: DateTime<Local>,
+	/// none if the snapshot cannot be read
+	pub contents: Option<persist::Contents>,
+}
+
+/// The snapshots shown by the save browser, out of all those in the folder
+#[derive(Clone, Debug)]
+pub struct SlotPage {
+	pub slots: Vec<SaveSlot>,
+	pub index: usize,
+	/// at least one, even if the folder is empty
+	pub count: usize,
+}
+
+/// What the save browser can do with a slot
+#[derive(Clone, Debug)]
+pub enum SlotAction {
+	Load,
+	Duplicate,
+	Rename(String),
+	Delete,
+}
+
+/// The name of a snapshot without the extension of its format
+fn stem_of(file_path: &path::Path, format: persist::Format) -> Option<String> {
+	file_path
+		.file_name()
+		.and_then(OsStr::to_str)
+		.map(|name| name[..name.len() - format.extension().len()].to_owned())
+}
+
+fn format_of(file_path: &path::Path) -> io::Result<persist::Format> {
+	persist::Format::from_path(file_path)
+		.ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "not a snapshot"))
+}
+
+/// Snapshots are read in full to get their contents, so only those on the requested page are.
+/// Pages past the last one show the last one
+pub fn list(containing_dir: &path::Path, page: usize) -> io::Result<SlotPage> {
+	let mut files = Vec::new();
+	for entry in fs::read_dir(containing_dir)? {
+		let entry = entry?;
+		let path = entry.path();
+		if persist::Format::from_path(&path).is_some() {
+			let modified: DateTime<Local> = entry.metadata()?.modified()?.into();
+			files.push((modified, path));
+		}
+	}
+	files.sort_by(|a, b| b.cmp(a));
+	let count = ((files.len() + SAVE_SLOTS_PER_PAGE - 1) / SAVE_SLOTS_PER_PAGE).max(1);
+	let index = page.min(count - 1);
+	let slots = files
+		.into_iter()
+		.skip(index * SAVE_SLOTS_PER_PAGE)
+		.take(SAVE_SLOTS_PER_PAGE)
+		.map(|(modified, path)| {
+			let contents = match persist::Serializer::peek(&path) {
+				Ok(contents) => Some(contents),
+				Err(e) => {
+					warn!("Could not read {:?}: {}", path, e);
+					None
+				}
+			};
+			SaveSlot {
+				name: path.file_name().and_then(OsStr::to_str).unwrap_or_default().to_owned(),
+				path,
+				modified,
+				contents,
+			}
+		}).collect();
+	Ok(SlotPage { slots, index, count })
+}
+
+/// Copies a snapshot next to itself, as `<name>_copy`, `<name>_copy2` and so on
+pub fn duplicate(file_path: &path::Path) -> io::Result<path::PathBuf> {
+	let format = format_of(file_path)?;
+	let stem = stem_of(file_path, format).unwrap_or_default();
+	let mut n = 1;
+	let copy_path = loop {
+		let suffix = if n == 1 { "_copy".to_owned() } else { format!("_copy{}", n) };
+		let copy_path = file_path.with_file_name(stem.clone() + &suffix + format.extension());
+		if !copy_path.exists() {
+			break copy_path;
+		}
+		n += 1;
+	};
+	fs::copy(file_path, &copy_path)?;
+	Ok(copy_path)
+}
+
+/// Renames a snapshot within its folder, keeping the extension of its format.
+/// Renamed snapshots are left alone by the retention policy
+pub fn rename(file_path: &path::Path, new_name: &str) -> io::Result<path::PathBuf> {
+	let format = format_of(file_path)?;
+	let new_name = new_name.trim();
+	if new_name.is_empty() || new_name.contains(|c: char| c == '/' || c == '\\') {
+		return Err(io::Error::new(io::ErrorKind::InvalidInput, "invalid snapshot name"));
+	}
+	let new_path = file_path.with_file_name(new_name.to_owned() + format.extension());
+	if new_path.exists() {
+		return Err(io::Error::new(io::ErrorKind::AlreadyExists, "a snapshot with that name exists"));
+	}
+	fs::rename(file_path, &new_path)?;
+	Ok(new_path)
+}
+
+pub fn delete(file_path: &path::Path) -> io::Result<()> {
+	format_of(file_path)?;
+	fs::remove_file(file_path)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use backend::testing::temp_dir;
+
+	fn touch(dir: &path::Path, name: &str) -> path::PathBuf {
+		let file_path = dir.join(name);
+		fs::write(&file_path, b"{}").unwrap();
+		file_path
+	}
+
+	#[test]
+	fn list_pages_through_all_snapshots() {
+		let dir = temp_dir("slots_list");
+		for i in 0..SAVE_SLOTS_PER_PAGE + 1 {
+			touch(&dir, &format!("{:02}.json", i));
+		}
+		touch(&dir, "notes.txt");
+		let first = list(&dir, 0).unwrap();
+		let last = list(&dir, 1).unwrap();
+		let past_the_end = list(&dir, 5).unwrap();
+		fs::remove_dir_all(&dir).unwrap();
+
+		assert_eq!(2, first.count);
+		assert_eq!(SAVE_SLOTS_PER_PAGE, first.slots.len());
+		assert_eq!((1, 1), (last.index, last.slots.len()));
+		assert_eq!((1, 1), (past_the_end.index, past_the_end.slots.len()));
+		assert!(first.slots.iter().all(|slot| slot.name.ends_with(".json")));
+	}
+
+	#[test]
+	fn an_empty_folder_has_one_empty_page() {
+		let dir = temp_dir("slots_empty");
+		let page = list(&dir, 0).unwrap();
+		fs::remove_dir_all(&dir).unwrap();
+		assert_eq!((0, 1, 0), (page.index, page.count, page.slots.len()));
+	}
+
+	#[test]
+	fn duplicates_get_numbered_copies() {
+		let dir = temp_dir("slots_duplicate");
+		let original = touch(&dir, "world.json.gz");
+		let first = duplicate(&original).unwrap();
+		let second = duplicate(&original).unwrap();
+		fs::remove_dir_all(&dir).unwrap();
+		assert_eq!(dir.join("world_copy.json.gz"), first);
+		assert_eq!(dir.join("world_copy2.json.gz"), second);
+	}
+
+	#[test]
+	fn rename_keeps_the_format_and_refuses_clashes() {
+		let dir = temp_dir("slots_rename");
+		let original = touch(&dir, "world.bin");
+		touch(&dir, "taken.bin");
+		let clash = rename(&original, "taken");
+		let invalid = rename(&original, "../elsewhere");
+		let renamed = rename(&original, " keeper ");
+		let exists = original.exists();
+		fs::remove_dir_all(&dir).unwrap();
+		assert!(clash.is_err());
+		assert!(invalid.is_err());
+		assert_eq!(dir.join("keeper.bin"), renamed.unwrap());
+		assert!(!exists);
+	}
+
+	#[test]
+	fn delete_only_removes_snapshots() {
+		let dir = temp_dir("slots_delete");
+		let snapshot = touch(&dir, "world.json");
+		let notes = touch(&dir, "notes.txt");
+		let deleted = delete(&snapshot);
+		let refused = delete(&notes);
+		let (snapshot_exists, notes_exist) = (snapshot.exists(), notes.exists());
+		fs::remove_dir_all(&dir).unwrap();
+		assert!(deleted.is_ok() && !snapshot_exists);
+		assert!(refused.is_err() && notes_exist);
+	}
+}
@@ -0,0 +1,254 @@
+use super::*;
+use backend::world::agent::TypedAgent;
+use backend::world::particle::Particle;
+use backend::world::topology::Topology;
+use backend::world::Feeder;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Instant;
+
+/// Everything the render loop needs to draw a frame, copied out of the app after an update.
+/// Snapshots are immutable once published, so they can be drawn while the next one is being simulated.
+/// Only what is drawn is copied, the agents themselves stay in the world
+pub struct Snapshot {
+	pub published: Instant,
+	pub frame_update: FrameUpdate,
+	pub camera: Position,
+	pub viewport_scale: f32,
+	pub viewport_ratio: f32,
+	pub is_running: bool,
+	pub is_capturing: bool,
+	pub has_ui_overlay: bool,
+	pub save_slots: Option<Arc<SlotPage>>,
+	pub(super) light_color: Rgba,
+	pub(super) background_color: Rgba,
+	pub(super) phase: Rgba,
+	pub(super) topology: Topology,
+	pub(super) swarms: HashMap<agent::AgentType, HashMap<Id, Sprite>>,
+	/// position and charge of the player, which lights up its surroundings
+	pub(super) player: Option<(Position, f32)>,
+	pub(super) particles: Vec<ParticleSprite>,
+	/// transform and intensity of each feeder
+	pub(super) feeders: Vec<(Transform, f32)>,
+	pub(super) selection_outline: Vec<Position>,
+	pub(super) debug_flags: DebugFlags,
+}
+
+pub(super) struct SegmentSprite {
+	pub transform: Transform,
+	pub radius: f32,
+	pub mesh: Arc<Mesh>,
+	pub color: Rgba,
+}
+
+/// What is drawn of an agent
+pub(super) struct Sprite {
+	pub position: Position,
+	pub energy_ratio: f32,
+	pub phase: f32,
+	pub infection_severity: Option<f32>,
+	/// in the order of their index
+	pub segments: Vec<SegmentSprite>,
+	/// only for selected minions, when debugging targets
+	pub trace: Option<Trace>,
+}
+
+/// What a selected minion is after and where it has been
+pub(super) struct Trace {
+	pub head: Transform,
+	pub head_radius: f32,
+	pub head_color: Rgba,
+	pub target_position: Position,
+	pub trajectory: Box<[Position]>,
+	pub intents: Vec<(Position, segment::Intent)>,
+}
+
+pub(super) struct ParticleSprite {
+	pub transform: Transform,
+	pub scale: f32,
+	pub color: Rgba,
+	pub effect: Rgba,
+	pub trail: Box<[Position]>,
+}
+
+impl Sprite {
+	fn new(agent: &agent::Agent, with_trace: bool) -> Self {
+		let trace = if with_trace && agent.state.selected() {
+			agent.first_segment(segment::Flags::HEAD).map(|head| Trace {
+				head: head.transform.clone(),
+				head_radius: head.growing_radius(),
+				head_color: head.color(),
+				target_position: agent.state.target_position(),
+				trajectory: agent.state.trajectory(),
+				intents: agent
+					.segments()
+					.iter()
+					.map(|segment| (segment.transform.position, segment.state.intent.clone()))
+					.collect(),
+			})
+		} else {
+			None
+		};
+		Sprite {
+			position: agent.transform().position,
+			energy_ratio: agent.state.energy_ratio(),
+			phase: agent.state.phase(),
+			infection_severity: agent.state.infection().map(|infection| infection.severity()),
+			segments: agent
+				.segments()
+				.iter()
+				.map(|segment| SegmentSprite {
+					transform: segment.transform.clone(),
+					radius: segment.growing_radius(),
+					mesh: segment.mesh.clone(),
+					color: segment.color(),
+				})
+				.collect(),
+			trace,
+		}
+	}
+}
+
+impl<'a> From<&'a Particle> for ParticleSprite {
+	fn from(particle: &'a Particle) -> Self {
+		ParticleSprite {
+			transform: particle.transform(),
+			scale: particle.scale(),
+			color: particle.color(),
+			effect: particle.effect(),
+			trail: particle.trail().to_vec().into_boxed_slice(),
+		}
+	}
+}
+
+impl App {
+	pub fn snapshot(&self, frame_update: FrameUpdate) -> Snapshot {
+		let with_trace = self.debug_flags.contains(DebugFlags::DEBUG_TARGETS);
+		Snapshot {
+			published: Instant::now(),
+			frame_update,
+			camera: self.camera.position(),
+			viewport_scale: self.viewport.scale,
+			viewport_ratio: self.viewport.ratio,
+			is_running: self.is_running,
+			is_capturing: self.is_capturing,
+			has_ui_overlay: self.has_ui_overlay,
+			save_slots: self.save_slots.clone(),
+			light_color: self.lights.get(),
+			background_color: self.backgrounds.get(),
+			phase: self.world.phase(),
+			topology: self.world.topology().clone(),
+			swarms: self
+				.world
+				.swarms()
+				.iter()
+				.map(|(agent_type, swarm)| {
+					let sprites = swarm
+						.agents()
+						.iter()
+						.map(|(id, agent)| (*id, Sprite::new(agent, with_trace)))
+						.collect();
+					(*agent_type, sprites)
+				})
+				.collect(),
+			player: self
+				.world
+				.get_player_segment()
+				.map(|segment| (segment.transform.position, segment.state.charge())),
+			particles: self.world.particles().iter().map(ParticleSprite::from).collect(),
+			feeders: self
+				.world
+				.feeders()
+				.iter()
+				.map(|feeder: &Feeder| (feeder.transform().clone(), feeder.intensity()))
+				.collect(),
+			selection_outline: self.selection_outline.clone(),
+			debug_flags: self.debug_flags,
+		}
+	}
+}
+
+/// The last two snapshots published by the simulation thread
+#[derive(Default)]
+pub struct SnapshotBuffer {
+	pair: Mutex<Option<(Arc<Snapshot>, Arc<Snapshot>)>>,
+}
+
+impl SnapshotBuffer {
+	/// The current snapshot becomes the previous one
+	pub fn publish(&self, snapshot: Snapshot) {
+		let snapshot = Arc::new(snapshot);
+		let mut pair = self.pair.lock().unwrap();
+		let previous = match *pair {
+			Some((_, ref current)) => current.clone(),
+			None => snapshot.clone(),
+		};
+		*pair = Some((previous, snapshot));
+	}
+
+	/// None until the first snapshot has been published
+	pub fn latest(&self) -> Option<Frame> {
+		let pair = self.pair.lock().unwrap();
+		pair.as_ref()
+			.map(|&(ref previous, ref current)| Frame::new(previous.clone(), current.clone(), Instant::now()))
+	}
+}
+
+fn duration_seconds(from: Instant, to: Instant) -> f32 {
+	if to > from {
+		let d = to.duration_since(from);
+		d.as_secs() as f32 + d.subsec_nanos() as f32 * 1e-9
+	} else {
+		0.
+	}
+}
+
+/// A frame to be drawn, moving from the previous snapshot to the current one.
+/// Drawing lags one update behind the simulation, in exchange for smooth motion at any frame rate
+pub struct Frame {
+	previous: Arc<Snapshot>,
+	current: Arc<Snapshot>,
+	alpha: f32,
+}
+
+impl Frame {
+	fn new(previous: Arc<Snapshot>, current: Arc<Snapshot>, now: Instant) -> Self {
+		let interval = duration_seconds(previous.published, current.published);
+		let alpha = if interval > 0. {
+			(duration_seconds(current.published, now) / interval).min(1.)
+		} else {
+			1.
+		};
+		Frame {
+			previous,
+			current,
+			alpha,
+		}
+	}
+
+	pub fn current(&self) -> &Snapshot { &self.current }
+
+	fn blend_position(&self, from: Position, to: Position) -> Position {
+		from + self.current.topology.delta(from, to) * self.alpha
+	}
+
+	/// Wrapping worlds are crossed the short way, and so are angles
+	fn blend_transform(&self, from: &Transform, to: &Transform) -> Transform {
+		let turn = to.angle - from.angle;
+		let turn = turn.sin().atan2(turn.cos());
+		Transform::new(self.blend_position(from.position, to.position), from.angle + turn * self.alpha)
+	}
+
+	pub fn camera_position(&self) -> Position { self.blend_position(self.previous.camera, self.current.camera) }
+
+	/// Where a segment is drawn, agents which have just appeared are not moved
+	pub(super) fn segment_transform(&self, id: Id, index: usize, segment: &SegmentSprite) -> Transform {
+		self.previous
+			.swarms
+			.get(&id.type_of())
+			.and_then(|sprites| sprites.get(&id))
+			.and_then(|previous| previous.segments.get(index))
+			.map(|previous| self.blend_transform(&previous.transform, &segment.transform))
+			.unwrap_or_else(|| segment.transform.clone())
+	}
+}
@@ -54,6 +54,8 @@ impl input::EventMapper<winit::WindowEvent> for WinitEventMapper {
 				Right -> Right,
 				PageUp -> PageUp,
 				PageDown -> PageDown,
+				LBracket -> OpenBracket,
+				RBracket -> CloseBracket,
 				LControl -> LCtrl,
 				RControl -> RCtrl,
 				LShift -> LShift,
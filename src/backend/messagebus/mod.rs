@@ -1,5 +1,6 @@
 use app::Event;
 use backend::world::alert::Alert;
+use backend::world::contact::ContactEvent;
 use backend::world::particle::Emitter;
 use std::sync::mpsc::{Sender, Receiver};
 use std::sync::mpsc;
@@ -9,6 +10,13 @@ pub enum Message {
 	Alert(Alert),
 	Event(Event),
 	NewEmitter(Emitter),
+	Contact(ContactEvent),
+}
+
+impl From<ContactEvent> for Message {
+	fn from(value: ContactEvent) -> Self {
+		Message::Contact(value)
+	}
 }
 
 impl From<Emitter> for Message {
@@ -38,6 +46,15 @@ impl Into<Option<Emitter>> for Message {
 	}
 }
 
+impl Into<Option<ContactEvent>> for Message {
+	fn into(self) -> Option<ContactEvent> {
+		match self {
+			Message::Contact(event) => Some(event),
+			_ => None,
+		}
+	}
+}
+
 impl Into<Option<Alert>> for Message {
 	fn into(self) -> Option<Alert> {
 		match self {
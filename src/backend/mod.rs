@@ -2,3 +2,5 @@ pub mod obj;
 pub mod world;
 pub mod systems;
 pub mod messagebus;
+#[cfg(test)]
+pub mod testing;
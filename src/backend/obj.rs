@@ -251,6 +251,10 @@ pub struct Material {
 	pub friction: f32,
 	pub linear_damping: f32,
 	pub angular_damping: f32,
+	/// scales the fluid resistance of each side of the shape
+	pub fluid_drag: f32,
+	/// force the joint holding the segment to its parent can take before breaking
+	pub joint_strength: f32,
 }
 
 #[derive(Clone)]
@@ -270,6 +274,8 @@ impl Default for Material {
 			friction: FRICTION_DEFAULT,
 			linear_damping: LINEAR_DAMPING_DEFAULT,
 			angular_damping: ANGULAR_DAMPING,
+			fluid_drag: FLUID_DRAG_DEFAULT,
+			joint_strength: JOINT_STRENGTH_UNBREAKABLE,
 		}
 	}
 }
@@ -7,8 +7,11 @@ use backend::world;
 use backend::world::agent;
 use backend::world::agent::TypedAgent;
 use backend::world::agent::Personality;
+use backend::world::agent::TouchResponse;
+use backend::world::gen;
 use backend::world::segment;
 use backend::world::segment::Intent;
+use backend::world::topology::Topology;
 use cgmath::*;
 use core::geometry::Position;
 use itertools::Itertools;
@@ -16,19 +19,49 @@ use std::collections::HashMap;
 use std::f32::consts;
 
 type IdPositionMap = HashMap<obj::Id, Position>;
+type SpottedMap = HashMap<obj::Id, (obj::Id, Position)>;
+/// What each minion is touching, by the id of whatever it touches
+type TouchMap = HashMap<obj::Id, HashMap<obj::Id, Touch>>;
+
+/// What a minion can tell about whoever touched it
+struct Touch {
+	kinship: f32,
+	is_mate: bool,
+	is_minion: bool,
+	position: Position,
+}
+
+impl Touch {
+	fn cues(&self) -> [f32; agent::N_WEIGHTS] {
+		fn signal(b: bool) -> f32 {
+			if b {
+				1.
+			} else {
+				-1.
+			}
+		}
+		[self.kinship * 2. - 1., signal(self.is_mate), signal(self.is_minion), 1.]
+	}
+}
 
 pub struct AiSystem {
+	topology: Topology,
 	beacons: Box<[Position]>,
 	targets: IdPositionMap,
+	spotted: SpottedMap,
+	touches: TouchMap,
 }
 
 impl System for AiSystem {
 	fn clear(&mut self) {
 		self.beacons = Box::new([]);
 		self.targets.clear();
+		self.spotted.clear();
+		self.touches.clear();
 	}
 
 	fn import(&mut self, world: &world::World) {
+		self.topology = world.topology().clone();
 		self.beacons = world
 			.feeders()
 			.iter()
@@ -41,12 +74,17 @@ impl System for AiSystem {
 			.filter(|&(_, ref v)| v.state.is_active())
 			.map(|(_, v)| (v.id(), v.transform().position))
 			.collect::<HashMap<_, _>>();
+		self.spotted = Self::find_nearest_targets(world);
+		self.touches = Self::find_touches(world);
 	}
 
 	fn export(&self, world: &mut world::World, _outbox: &Outbox) {
 		Self::update_minions(
+			&self.topology,
 			&self.targets,
+			&self.spotted,
 			&self.beacons,
+			&self.touches,
 			&mut world.agents_mut(agent::AgentType::Minion),
 		);
 	}
@@ -55,19 +93,81 @@ impl System for AiSystem {
 impl Default for AiSystem {
 	fn default() -> Self {
 		AiSystem {
+			topology: Topology::default(),
 			beacons: Box::new([]),
 			targets: HashMap::new(),
+			spotted: HashMap::new(),
+			touches: HashMap::new(),
 		}
 	}
 }
 
+fn radar_range(sensor: &segment::Segment) -> f32 { sensor.growing_radius() * RADAR_RANGE_RATIO }
+
 impl AiSystem {
-	fn update_minions(targets: &IdPositionMap, beacons: &[Position], minions: &mut agent::AgentMap) {
-		fn nearest_beacon<'a>(beacons: &'a [Position], p: &'a Position) -> &'a Position {
+	/// The closest resource within radar range of each minion's sensor
+	fn find_nearest_targets(world: &world::World) -> SpottedMap {
+		let index = world.index();
+		world
+			.agents(agent::AgentType::Minion)
+			.values()
+			.filter_map(|agent| {
+				agent.first_segment(segment::Flags::SENSOR).and_then(|sensor| {
+					index
+						.nearest_one(agent::AgentType::Resource, sensor.transform.position, radar_range(&sensor))
+						.map(|e| (agent.id(), (e.id, e.position)))
+				})
+			}).collect()
+	}
+
+	fn find_touches(world: &world::World) -> TouchMap {
+		let mut touches = HashMap::new();
+		for agent in world.agents(agent::AgentType::Minion).values() {
+			for segment in agent.segments() {
+				if let Some(key) = segment.state.last_touched {
+					let other_id = key.id();
+					if other_id.type_of() == agent::AgentType::Resource {
+						continue;
+					}
+					if let Some(other) = world.agent(other_id) {
+						let has_genes = match other_id.type_of() {
+							agent::AgentType::Minion | agent::AgentType::Spore => true,
+							_ => false,
+						};
+						touches.entry(agent.id()).or_insert_with(HashMap::new).insert(
+							other_id,
+							Touch {
+								kinship: if has_genes {
+									gen::similarity(agent.dna(), other.dna())
+								} else {
+									0.
+								},
+								is_mate: has_genes && other.gender() != agent.gender(),
+								is_minion: other_id.type_of() == agent::AgentType::Minion,
+								position: other.transform().position,
+							},
+						);
+					}
+				}
+			}
+		}
+		touches
+	}
+
+	fn update_minions(
+		topology: &Topology,
+		targets: &IdPositionMap,
+		spotted: &SpottedMap,
+		beacons: &[Position],
+		touches: &TouchMap,
+		minions: &mut agent::AgentMap,
+	)
+	{
+		fn nearest_beacon<'a>(topology: &Topology, beacons: &'a [Position], p: &'a Position) -> &'a Position {
 			beacons
 				.iter()
 				.fold1(|n, b| {
-					if (p - n).magnitude2() < (p - b).magnitude2() {
+					if topology.delta(*p, *n).magnitude2() < topology.delta(*p, *b).magnitude2() {
 						n
 					} else {
 						b
@@ -75,34 +175,30 @@ impl AiSystem {
 				}).unwrap_or(p)
 		}
 
-		for (_, agent) in minions.iter_mut() {
+		for (&id, agent) in minions.iter_mut() {
 			let brain = agent.brain().clone();
 			let core = agent.first_segment(segment::Flags::CORE);
 			let head = agent.first_segment(segment::Flags::SENSOR);
 			if let Some(sensor) = head {
-				let p0 = sensor.transform.position;
-				let radar_range = sensor.growing_radius() * 10.;
+				let radar_range = radar_range(&sensor);
 				let current_target = *agent.state.target();
 				let current_target_position = agent.state.target_position();
 				// if our original target is dead then we need to find another one
 				let new_target: Option<(obj::Id, Position)> = match current_target {
-					None => targets
-						.iter()
-						.find(|&(_, &p)| (p - p0).magnitude() < radar_range)
-						.map(|(&id, &position)| (id, position)),
+					None => spotted.get(&id).cloned(),
 					Some(id) => targets.get(&id).map(|&position| (id, position)),
 				};
 				// and failing that again, we target
 				match new_target {
 					None => agent
 						.state
-						.retarget(None, *nearest_beacon(beacons, &current_target_position)),
+						.retarget(None, *nearest_beacon(topology, beacons, &current_target_position)),
 					Some((id, position)) => agent.state.retarget(Some(id), position),
 				};
 				// find where our target is in the world
 				let target_position = agent.state.target_position();
 				// and transform the world position into the head's frame
-				let t0 = target_position - sensor.transform.position;
+				let t0 = topology.delta(sensor.transform.position, target_position);
 				let t = t0.normalize_to(t0.magnitude().min(radar_range));
 				// direction in which the head is pointing, normalized
 				let s = Matrix2::from_angle(Rad(sensor.transform.angle)) * (-Position::unit_y());
@@ -110,10 +206,15 @@ impl AiSystem {
 				// torso
 				let neck_angle = consts::PI + sensor.transform.angle
 					- core.map(|t| t.transform.angle).unwrap_or(sensor.transform.angle);
+				// the closest relative currently touching us, if any
+				let kin_cue = touches
+					.get(&id)
+					.and_then(|touching| touching.values().map(|touch| touch.kinship).fold1(f32::max))
+					.unwrap_or(0.);
 				// we pass the relative position of the target decomposed in our frame of
 				// reference to the neural network expecting four components we can use as
-				// thresholds
-				let r = agent.brain().response(&[neck_angle, t.dot(s), t.perp_dot(s), 0.]);
+				// thresholds, plus how closely related we are to whoever is touching us
+				let r = agent.brain().response(&[neck_angle, t.dot(s), t.perp_dot(s), kin_cue]);
 
 				let segments = &mut agent.segments_mut();
 				let mut touch_accumulator = 0.0f32;
@@ -122,26 +223,46 @@ impl AiSystem {
 					if flags.contains(segment::Flags::ACTUATOR) {
 						let power = segment.state.charge() * segment.growing_radius().powi(2) * POWER_BOOST;
 						let f = Matrix2::from_angle(Rad(segment.transform.angle)) * Position::unit_y() * power;
-						let intent = if let Some(refs) = segment.state.last_touched {
-							match refs.id().type_of() {
-								agent::AgentType::Resource => Intent::Idle,
-								_ => {
-									let fear: f32 = brain.fear();
-									touch_accumulator += COLLISION_BASE_COST / segment.state.maturity();
-									Intent::RunAway(f * fear)
+						let reaction = segment.state.last_touched.map(|refs| match refs.id().type_of() {
+							agent::AgentType::Resource => None,
+							_ => Some(match touches.get(&id).and_then(|touching| touching.get(&refs.id())) {
+								Some(touch) => (brain.touch_response(&touch.cues()), Some(touch.position)),
+								// walls and anything else we can't recognize
+								None => (TouchResponse::Flee, None),
+							}),
+						});
+						// a touch takes priority over what the brain would do otherwise
+						let intent = match reaction {
+							// touching a resource
+							Some(None) => Intent::Idle,
+							Some(Some((response, position))) if response != TouchResponse::Ignore => {
+								let fear: f32 = brain.fear();
+								let towards = position
+									.map(|p| topology.delta(segment.transform.position, p))
+									.filter(|v| v.magnitude2() > 0.)
+									.map(|v| v.normalize_to(power))
+									.unwrap_or(f);
+								touch_accumulator += COLLISION_BASE_COST / segment.state.maturity();
+								match response {
+									TouchResponse::Follow => Intent::Move(towards),
+									TouchResponse::Push => Intent::RunAway(towards * fear),
+									_ => Intent::RunAway(f * fear),
 								}
 							}
-						} else if (flags.contains(segment::Flags::RUDDER | segment::Flags::LEFT)
-							&& r[0] > brain.hunger())
-							|| (flags.contains(segment::Flags::RUDDER | segment::Flags::RIGHT) && r[1] > brain.hunger())
-						{
-							Intent::Move(-f)
-						} else if flags.contains(segment::Flags::THRUSTER) && r[2] > brain.haste() {
-							Intent::Move(f)
-						} else if flags.contains(segment::Flags::BRAKE) && r[3] > brain.prudence() {
-							Intent::Brake(-f)
-						} else {
-							Intent::Idle
+							// not touching anything, or ignoring it
+							_ => if (flags.contains(segment::Flags::RUDDER | segment::Flags::LEFT)
+								&& r[0] > brain.hunger())
+								|| (flags.contains(segment::Flags::RUDDER | segment::Flags::RIGHT)
+									&& r[1] > brain.hunger())
+							{
+								Intent::Move(-f)
+							} else if flags.contains(segment::Flags::THRUSTER) && r[2] > brain.haste() {
+								Intent::Move(f)
+							} else if flags.contains(segment::Flags::BRAKE) && r[3] > brain.prudence() {
+								Intent::Brake(-f)
+							} else {
+								Intent::Idle
+							},
 						};
 						match intent {
 							Intent::Idle => segment.state.set_target_charge(brain.rest()),
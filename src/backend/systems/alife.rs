@@ -1,24 +1,32 @@
 use super::*;
 use app::constants::*;
-use backend::messagebus::Outbox;
+use backend::messagebus::{Inbox, Message, Outbox, PubSub, ReceiveDrain, Whiteboard};
 use backend::obj;
 use backend::obj::Identified;
 use backend::obj::Transformable;
 use backend::world;
 use backend::world::agent;
+use backend::world::agent::TypedAgent;
 use backend::world::alert;
+use backend::world::contact::ContactEvent;
 use backend::world::gen;
+use backend::world::index;
 use backend::world::particle;
 use backend::world::segment;
+use backend::world::topology;
 use backend::world::AgentState;
 use core::clock::SimulationTimer;
 use core::geometry;
+use num::Zero;
 use rand;
+use rand::Rng;
+use std::f32::consts;
 use serialize::base64::{self, ToBase64};
 use std::collections::HashMap;
 
 type StateMap = HashMap<obj::Id, agent::State>;
 type GeneMap = HashMap<obj::Id, gen::Dna>;
+type DensityMap = HashMap<obj::Id, f32>;
 
 pub struct AlifeSystem {
 	dt: Seconds,
@@ -26,16 +34,34 @@ pub struct AlifeSystem {
 	source: Box<[world::Feeder]>,
 	eaten: StateMap,
 	touched: GeneMap,
+	food: DensityMap,
+	/// impulse taken in collisions by each minion since the last update
+	bruises: DensityMap,
+	inbox: Option<Inbox>,
 }
 
 impl System for AlifeSystem {
+	fn attach(&mut self, bus: &mut PubSub) {
+		self.inbox = Some(bus.subscribe(Box::new(|m| match *m {
+			Message::Contact(ContactEvent::Impulse(_)) => true,
+			_ => false,
+		})));
+	}
+
 	fn clear(&mut self) {
 		self.source = Box::new([]);
 		self.eaten.clear();
 		self.touched.clear();
+		self.food.clear();
+		self.bruises.clear();
 	}
 
 	fn import(&mut self, world: &world::World) {
+		let messages = match self.inbox {
+			Some(ref m) => m.drain(),
+			None => Vec::new(),
+		};
+		self.bruises = Self::find_bruises(messages);
 		self.source = world.feeders().to_vec().into_boxed_slice();
 		self.eaten = Self::find_eaten_resources(
 			&world.agents(agent::AgentType::Minion),
@@ -45,6 +71,7 @@ impl System for AlifeSystem {
 			&world.agents(agent::AgentType::Minion),
 			&world.agents(agent::AgentType::Spore),
 		);
+		self.food = Self::find_food_density(world.index(), &world.agents(agent::AgentType::Spore));
 	}
 
 	fn update(&mut self, _: &AgentState, dt: Seconds) {
@@ -60,29 +87,37 @@ impl System for AlifeSystem {
 			&self.eaten,
 		);
 
+		let topology = world.topology().clone();
 		let MinionEndState(spores, corpses) = Self::update_minions(
 			outbox,
 			self.dt,
-			world.extent,
+			&topology,
 			&mut world.agents_mut(agent::AgentType::Minion),
 			&self.eaten,
+			&self.bruises,
 		);
 
-		let SporeEndState(hatch, fertilised) = Self::update_spores(
+		let SporeEndState(hatch, withered, fertilised) = Self::update_spores(
 			self.dt,
 			&self.simulation_timer,
 			&mut world.agents_mut(agent::AgentType::Spore),
 			&self.touched,
+			&self.food,
 		);
 
-		for (transform, dna) in &*spores {
+		for &(ref transform, ref dna, lineage) in &*spores {
 			outbox.post(alert::Alert::NewSpore.into());
-			world.new_spore(outbox, transform.clone(), dna);
+			world.new_spore(outbox, transform.clone(), dna, lineage);
 		}
 
-		for (transform, dna) in &*hatch {
+		for &(ref transform, ref dna, lineage) in &*hatch {
 			outbox.post(alert::Alert::NewMinion.into());
-			world.hatch_spore(outbox, transform.clone(), dna);
+			world.hatch_spore(outbox, transform.clone(), dna, lineage);
+		}
+
+		// spores which never found the right conditions feed the others
+		for (transform, dna) in &*withered {
+			world.decay_to_resource(outbox, transform.clone(), dna);
 		}
 
 		for (transforms, dna) in &*corpses {
@@ -106,16 +141,23 @@ impl Default for AlifeSystem {
 			source: Box::new([]),
 			eaten: StateMap::new(),
 			touched: GeneMap::new(),
+			food: DensityMap::new(),
+			bruises: DensityMap::new(),
+			inbox: None,
 		}
 	}
 }
 
 struct MinionEndState(
-	Box<[(geometry::Transform, gen::Dna)]>,
+	Box<[(geometry::Transform, gen::Dna, obj::Id)]>,
 	Box<[(Box<[geometry::Transform]>, gen::Dna)]>,
 );
 
-struct SporeEndState(Box<[(geometry::Transform, gen::Dna)]>, usize);
+struct SporeEndState(
+	Box<[(geometry::Transform, gen::Dna, obj::Id)]>,
+	Box<[(geometry::Transform, gen::Dna)]>,
+	usize,
+);
 
 impl AlifeSystem {
 	fn find_eaten_resources(minions: &agent::AgentMap, resources: &agent::AgentMap) -> StateMap {
@@ -155,12 +197,36 @@ impl AlifeSystem {
 		touched
 	}
 
+	fn find_bruises(messages: Vec<Message>) -> DensityMap {
+		let mut bruises = HashMap::new();
+		for message in messages {
+			if let Message::Contact(ContactEvent::Impulse(contact)) = message {
+				if contact.segment.id().type_of() == agent::AgentType::Minion {
+					*bruises.entry(contact.segment.id()).or_insert(0.) += contact.impulse;
+				}
+			}
+		}
+		bruises
+	}
+
+	fn find_food_density(index: &index::SpatialIndex, spores: &agent::AgentMap) -> DensityMap {
+		let mut food = HashMap::new();
+		for spore in spores.values().filter(|&a| a.state.is_active()) {
+			let count = index
+				.within(agent::AgentType::Resource, spore.transform().position, SPORE_SENSE_RADIUS)
+				.len();
+			food.insert(spore.id(), count as f32);
+		}
+		food
+	}
+
 	fn update_minions(
 		outbox: &Outbox,
 		dt: Seconds,
-		extent: geometry::Rect,
+		topology: &topology::Topology,
 		minions: &mut agent::AgentMap,
 		eaten: &StateMap,
+		bruises: &DensityMap,
 	) -> MinionEndState
 	{
 		let mut spawns = Vec::new();
@@ -189,12 +255,16 @@ impl AlifeSystem {
 						}
 					}
 				} else if agent.state.consume_ratio(SPAWN_COST_THRESHOLD, SPAWN_COST_RATIO) {
-					spawns.push((agent.last_segment().transform().clone(), agent.dna().clone()));
+					spawns.push((agent.last_segment().transform().clone(), agent.dna().clone(), agent.lineage()));
+				}
+
+				// collisions cost energy
+				if let Some(impulse) = bruises.get(&id) {
+					agent.state.consume(impulse * COLLISION_IMPULSE_COST);
 				}
 
 				for segment in agent.segments.iter_mut() {
-					let p = segment.transform().position;
-					if p.x < extent.min.x || p.x > extent.max.x || p.y < extent.min.y || p.y > extent.max.y {
+					if !topology.contains(segment.transform().position) {
 						agent.state.die();
 					}
 					if segment.flags.contains(segment::Flags::MOUTH) {
@@ -258,18 +328,34 @@ impl AlifeSystem {
 		timer: &SimulationTimer,
 		spores: &mut agent::AgentMap,
 		touched: &GeneMap,
+		food: &DensityMap,
 	) -> SporeEndState
 	{
+		let rng = &mut rand::thread_rng();
 		let mut spawns = Vec::new();
+		let mut withered = Vec::new();
 		let mut fertilise_count = 0usize;
 		for (spore_id, spore) in spores.iter_mut() {
-			if spore.state.lifecycle().is_expired(timer) {
+			let (window, food_trigger, drift) = match spore.dormancy() {
+				Some(dormancy) => (dormancy.window, dormancy.food_trigger, dormancy.drift),
+				None => (Seconds::zero(), 0., 0.),
+			};
+			let left = spore.state.lifecycle().left(timer);
+			let is_ripe = left <= window;
+			let is_triggered = food.get(spore_id).cloned().unwrap_or(0.) >= food_trigger;
+			if !spore.state.is_active() {
+				continue;
+			} else if is_ripe && is_triggered {
 				spore.state.die();
 				spawns.push((
 					spore.transform().clone(),
 					Self::crossover(spore.dna(), spore.state.foreign_dna()),
+					spore.lineage(),
 				))
-			} else if spore.state.is_active() {
+			} else if spore.state.lifecycle().is_expired(timer) {
+				spore.state.die();
+				withered.push((spore.transform().clone(), spore.dna().clone()));
+			} else {
 				for segment in spore.segments.iter_mut() {
 					if let Some(key) = segment.state.last_touched {
 						if let Some(touched_dna) = touched.get(&key.id()) {
@@ -284,11 +370,19 @@ impl AlifeSystem {
 						}
 					}
 				}
+				// passive random walk
+				let angle = rng.gen::<f32>() * consts::PI * 2.;
+				let force = geometry::Position::new(angle.cos(), angle.sin()) * drift;
 				for segment in spore.segments.iter_mut() {
+					segment.state.intent = segment::Intent::Move(force);
 					segment.state.update(dt)
 				}
 			}
 		}
-		SporeEndState(spawns.into_boxed_slice(), fertilise_count)
+		SporeEndState(
+			spawns.into_boxed_slice(),
+			withered.into_boxed_slice(),
+			fertilise_count,
+		)
 	}
 }
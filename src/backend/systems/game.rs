@@ -82,6 +82,10 @@ impl System for GameSystem {
 			None => Vec::new(),
 		};
 
+		let weapon = world.weapon();
+		self.playerstate.bullet_speed = weapon.bullet_speed;
+		self.playerstate.firing_rate = weapon.firing_rate;
+		self.playerstate.bullet_charge = weapon.bullet_charge;
 		for message in messages {
 			if let Message::Event(Event::PrimaryFire(bullet_speed, rate)) = message {
 				self.primary_fire(bullet_speed, rate);
@@ -90,10 +94,15 @@ impl System for GameSystem {
 
 		let source = world.feeders();
 		// Add missing emitters - deletion not supported
+		// emitters restored from a snapshot carry on from where they were
 		for s in &source[self.feeders.len()..] {
-			//			let s = &source[i];
-			self.feeders
-				.push(Feeder::new(s.transform().position, s.rate(), &self.timer));
+			let mut feeder = Feeder::new(s.transform().position, s.rate(), &self.timer);
+			if let Some(countdown) = s.countdown() {
+				feeder.hourglass.delay(countdown - s.rate());
+			}
+			feeder.angle = s.transform().angle;
+			feeder.light_intensity.force_to(s.intensity());
+			self.feeders.push(feeder);
 		}
 		for (i, d) in self.feeders.iter_mut().enumerate() {
 			d.position = source[i].transform().position;
@@ -151,8 +160,15 @@ impl System for GameSystem {
 		for (src, dest) in self.feeders.iter().zip(world.feeders_mut().iter_mut()) {
 			dest.transform_to(Transform::new(src.position, src.angle));
 			dest.set_intensity(src.light_intensity.get());
+			dest.set_countdown(src.hourglass.left(&self.timer));
 		}
 
+		world.set_weapon(world::Weapon {
+			bullet_speed: self.playerstate.bullet_speed,
+			firing_rate: self.playerstate.firing_rate,
+			bullet_charge: self.playerstate.bullet_charge,
+		});
+
 		if self.playerstate.bullet_ready {
 			world.primary_fire(outbox, self.playerstate.bullet_speed);
 		}
@@ -171,6 +187,11 @@ impl System for GameSystem {
 			world.init_minions();
 		}
 
+		// props are static scenery, respawn them whenever the world is cleared
+		if world.agents(agent::AgentType::Prop).is_empty() && !world.scenario().props.is_empty() {
+			world.init_props();
+		}
+
 		// if there are no players, spawn one
 		if world.agents(agent::AgentType::Player).is_empty() {
 			world.init_players();
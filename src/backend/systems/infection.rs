@@ -0,0 +1,101 @@
+use super::*;
+use app::constants::*;
+use backend::obj;
+use backend::obj::Identified;
+use backend::world;
+use backend::world::agent;
+use backend::world::infection::Pathogen;
+use backend::world::AgentState;
+use rand;
+use rand::Rng;
+use std::collections::HashMap;
+
+type PathogenMap = HashMap<obj::Id, Pathogen>;
+
+pub struct InfectionSystem {
+	dt: Seconds,
+	exposed: PathogenMap,
+}
+
+impl System for InfectionSystem {
+	fn clear(&mut self) { self.exposed.clear(); }
+
+	fn import(&mut self, world: &world::World) {
+		self.exposed = Self::find_exposed(&world.agents(agent::AgentType::Minion));
+	}
+
+	fn update(&mut self, _: &AgentState, dt: Seconds) { self.dt = dt; }
+
+	fn export(&self, world: &mut world::World, _outbox: &Outbox) {
+		Self::update_minions(self.dt, &mut world.agents_mut(agent::AgentType::Minion), &self.exposed);
+	}
+}
+
+impl Default for InfectionSystem {
+	fn default() -> Self {
+		InfectionSystem {
+			dt: Seconds::new(1. / 60.),
+			exposed: PathogenMap::new(),
+		}
+	}
+}
+
+impl InfectionSystem {
+	/// Healthy minions which have touched an infected one, with the pathogen they might catch
+	fn find_exposed(minions: &agent::AgentMap) -> PathogenMap {
+		let mut exposed = HashMap::new();
+		for agent in minions
+			.values()
+			.filter(|&a| a.state.is_active() && !a.state.is_infected())
+		{
+			for segment in agent.segments.iter() {
+				if let Some(key) = segment.state.last_touched {
+					if let Some(infection) = minions.get(&key.id()).and_then(|other| other.state.infection()) {
+						exposed.insert(agent.id(), infection.pathogen().clone());
+					}
+				}
+			}
+		}
+		exposed
+	}
+
+	fn update_minions(dt: Seconds, minions: &mut agent::AgentMap, exposed: &PathogenMap) {
+		let rng = &mut rand::thread_rng();
+		// once in a while a new disease appears out of nowhere
+		// chosen among healthy active minions only, or the outbreak would fizzle out at once
+		let patient_zero = if rng.gen::<f32>() < PATHOGEN_SPONTANEOUS_RATE * dt.get() as f32 {
+			let candidates = minions
+				.iter()
+				.filter(|&(_, a)| a.state.is_active() && a.state.is_alive() && !a.state.is_infected())
+				.map(|(id, _)| *id)
+				.collect::<Vec<_>>();
+			rng.choose(&candidates).cloned()
+		} else {
+			None
+		};
+
+		for (id, agent) in minions.iter_mut().filter(|&(_, ref a)| a.state.is_active()) {
+			let immunity = agent.immunity();
+			if Some(*id) == patient_zero && !agent.state.is_infected() {
+				agent.state.infect(Pathogen::random(rng));
+			} else if let Some(pathogen) = exposed.get(id) {
+				if rng.gen::<f32>() < pathogen.transmissibility * (1. - immunity / IMMUNITY_MAX) {
+					agent.state.infect(pathogen.replicate(rng));
+				}
+			}
+			let tax = match agent.state.infection_mut() {
+				Some(infection) => {
+					infection.update(dt, immunity);
+					Some((dt * infection.pathogen().virulence, infection.is_over()))
+				}
+				None => None,
+			};
+			if let Some((energy, is_over)) = tax {
+				agent.state.consume(energy);
+				if is_over {
+					agent.state.recover();
+				}
+			}
+		}
+	}
+}
@@ -3,11 +3,13 @@ pub mod animation;
 pub mod ai;
 pub mod alife;
 pub mod game;
+pub mod infection;
 pub mod particle;
 
 pub use self::physics::PhysicsSystem;
 pub use self::animation::AnimationSystem;
 pub use self::game::GameSystem;
+pub use self::infection::InfectionSystem;
 pub use self::ai::AiSystem;
 pub use self::alife::AlifeSystem;
 pub use self::particle::ParticleSystem;
@@ -36,3 +38,83 @@ pub trait System {
 		self.export(world, outbox)
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use backend::obj::Transformable;
+	use backend::testing;
+	use backend::world::agent::AgentType;
+	use backend::world::segment;
+	use core::clock::seconds;
+
+	/// Runs the AI and alife systems on top of the circle physics backend, as the app does
+	fn run_frames(world: &mut world::World, systems: &mut [&mut System], frames: usize) {
+		let mut bus = PubSub::new();
+		for system in systems.iter_mut() {
+			system.attach(&mut bus);
+			system.init(world);
+		}
+		for _ in 0..frames {
+			let registered: Vec<_> = world.registered().iter().filter_map(|&id| world.agent(id)).cloned().collect();
+			for system in systems.iter_mut() {
+				for agent in &registered {
+					system.register(agent);
+				}
+			}
+			let dt = seconds(1. / 60.);
+			world.tick(dt);
+			for system in systems.iter_mut() {
+				system.step(world, dt);
+			}
+			for system in systems.iter() {
+				system.apply(world, &bus);
+			}
+			let swept = world.sweep();
+			for system in systems.iter_mut() {
+				for agent in &*swept {
+					system.unregister(agent);
+				}
+			}
+			world.reindex();
+		}
+	}
+
+	#[test]
+	fn minions_without_food_head_for_the_nearest_feeder() {
+		let mut world = testing::populated_world();
+		let mut physics = PhysicsSystem::<physics::CircleBackend>::default();
+		let mut ai = AiSystem::default();
+		run_frames(&mut world, &mut [&mut physics as &mut System, &mut ai], 1);
+
+		let feeders: Vec<_> = world.feeders().iter().map(|feeder| feeder.transform().position).collect();
+		let mut sensing = 0;
+		for agent in world.agents(AgentType::Minion).values() {
+			if agent.first_segment(segment::Flags::SENSOR).is_some() {
+				sensing += 1;
+				assert_eq!(None, *agent.state.target());
+				assert!(feeders.contains(&agent.state.target_position()), "minion {}", agent.id());
+			}
+		}
+		assert!(sensing > 0);
+	}
+
+	#[test]
+	fn minions_without_food_spend_their_energy() {
+		let mut world = testing::populated_world();
+		let total_energy = |world: &world::World| -> f32 {
+			world.agents(AgentType::Minion).values().map(|agent| agent.state.energy()).sum()
+		};
+		let before = total_energy(&world);
+		let mut physics = PhysicsSystem::<physics::CircleBackend>::default();
+		let mut ai = AiSystem::default();
+		let mut alife = AlifeSystem::default();
+		run_frames(&mut world, &mut [&mut physics as &mut System, &mut ai, &mut alife], 60);
+
+		assert!(total_energy(&world) < before);
+		for agent in world.agents(AgentType::Minion).values() {
+			let position = agent.transform().position;
+			assert!(position.x.is_finite() && position.y.is_finite(), "minion {}", agent.id());
+		}
+	}
+}
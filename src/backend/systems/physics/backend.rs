@@ -0,0 +1,203 @@
+use backend::world::agent;
+use backend::world::contact::{Contact, ContactEvent};
+use core::geometry::*;
+use std::collections::HashMap;
+use std::hash::Hash;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BodyType {
+	Static,
+	Kinematic,
+	Dynamic,
+}
+
+#[derive(Clone)]
+pub struct BodyDef {
+	pub key: agent::Key,
+	pub body_type: BodyType,
+	pub transform: Transform,
+	pub motion: Motion,
+	pub linear_damping: f32,
+	pub angular_damping: f32,
+}
+
+/// Shapes in body coordinates
+#[derive(Clone, Debug)]
+pub enum ShapeDef {
+	Circle { radius: f32 },
+	/// convex, counter clockwise
+	Polygon { vertices: Box<[Position]> },
+	/// closed outline, only meant for static bodies
+	Loop { vertices: Box<[Position]> },
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct FixtureDef {
+	pub key: agent::Key,
+	pub density: f32,
+	pub restitution: f32,
+	pub friction: f32,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum JointKind {
+	Revolute { lower_angle: f32, upper_angle: f32 },
+	Weld { frequency: f32, damping_ratio: f32 },
+}
+
+/// The segment held by a joint, and the force the joint can take before breaking
+#[derive(Clone, Copy, Debug)]
+pub struct JointLimit {
+	pub distal: agent::Key,
+	pub strength: f32,
+}
+
+/// Joins body b to body a, anchors in the coordinates of their bodies.
+/// Jointed bodies don't collide with each other
+#[derive(Clone, Copy)]
+pub struct JointDef<H> {
+	pub kind: JointKind,
+	pub body_a: H,
+	pub body_b: H,
+	pub anchor_a: Position,
+	pub anchor_b: Position,
+	pub reference_angle: f32,
+	pub limit: JointLimit,
+}
+
+/// Snapshot of a body, as left by the last step
+#[derive(Clone)]
+pub struct BodyState {
+	pub key: agent::Key,
+	pub transform: Transform,
+	pub motion: Motion,
+	pub center: Position,
+	/// zero for static and kinematic bodies
+	pub mass: f32,
+	/// around the center of mass
+	pub inertia: f32,
+}
+
+impl BodyState {
+	pub fn world_point(&self, local: Position) -> Position { self.transform.apply(local) }
+
+	pub fn world_vector(&self, local: Position) -> Position { self.transform.apply_rotation(local) }
+
+	/// Velocity of a point attached to the body, in world coordinates
+	pub fn velocity_at(&self, point: Position) -> Position {
+		let r = point - self.center;
+		self.motion.velocity + Position::new(-r.y, r.x) * self.motion.spin
+	}
+}
+
+/// Contact events collected by a backend during the steps of an update, ready to be published
+#[derive(Default)]
+pub struct ContactLog {
+	pub events: Vec<ContactEvent>,
+	pub impulses: HashMap<(agent::Key, agent::Key), Contact>,
+}
+
+impl ContactLog {
+	pub fn clear(&mut self) {
+		self.events.clear();
+		self.impulses.clear();
+	}
+
+	/// Contacts between segments of the same agent are ignored
+	pub fn begin(&mut self, segment: agent::Key, partner: agent::Key, point: Position, normal: Position) {
+		if segment.agent_id != partner.agent_id {
+			let contact = Contact {
+				segment,
+				partner,
+				point,
+				normal,
+				impulse: 0.,
+			};
+			self.events.push(ContactEvent::Begin(contact.mirror()));
+			self.events.push(ContactEvent::Begin(contact));
+		}
+	}
+
+	pub fn end(&mut self, segment: agent::Key, partner: agent::Key) {
+		if segment.agent_id != partner.agent_id {
+			self.events.push(ContactEvent::End(segment, partner));
+			self.events.push(ContactEvent::End(partner, segment));
+		}
+	}
+
+	/// Impulses of all the steps in an update add up
+	pub fn solve(&mut self, segment: agent::Key, partner: agent::Key, point: Position, normal: Position, impulse: f32) {
+		if segment.agent_id == partner.agent_id {
+			return;
+		}
+		let contact = Contact {
+			segment,
+			partner,
+			point,
+			normal,
+			impulse,
+		};
+		for contact in vec![contact.mirror(), contact] {
+			let entry = self.impulses.entry((contact.segment, contact.partner)).or_insert_with(|| Contact {
+				impulse: 0.,
+				..contact.clone()
+			});
+			entry.point = contact.point;
+			entry.normal = contact.normal;
+			entry.impulse += contact.impulse;
+		}
+	}
+
+	/// The partner pushing hardest against each segment
+	pub fn strongest(&self) -> HashMap<agent::Key, (agent::Key, f32)> {
+		let mut strongest = HashMap::new();
+		for contact in self.impulses.values() {
+			let entry = strongest.entry(contact.segment).or_insert((contact.partner, contact.impulse));
+			if contact.impulse > entry.1 {
+				*entry = (contact.partner, contact.impulse);
+			}
+		}
+		strongest
+	}
+}
+
+/// A rigid body simulation the `PhysicsSystem` can drive.
+/// Bodies and fixtures carry the agent keys they stand for, which come back from queries and contacts
+pub trait PhysicsBackend: Default {
+	type Body: Copy + Eq + Hash;
+	type Joint: Copy + Eq + Hash;
+
+	/// Destroys all bodies and joints, pending contacts included
+	fn clear(&mut self);
+
+	fn create_body(&mut self, def: &BodyDef) -> Self::Body;
+	/// Joints attached to the body go with it
+	fn destroy_body(&mut self, body: Self::Body);
+	fn add_fixture(&mut self, body: Self::Body, shape: &ShapeDef, def: &FixtureDef);
+	fn clear_fixtures(&mut self, body: Self::Body);
+
+	fn create_joint(&mut self, def: &JointDef<Self::Body>) -> Self::Joint;
+	fn destroy_joint(&mut self, joint: Self::Joint);
+	fn body_joints(&self, body: Self::Body) -> Vec<Self::Joint>;
+	/// Every joint with its limit and the reaction force it applied during the last step
+	fn joint_loads(&self, inv_dt: f32) -> Vec<(Self::Joint, JointLimit, f32)>;
+
+	fn bodies(&self) -> Vec<(Self::Body, BodyState)>;
+	fn body(&self, body: Self::Body) -> BodyState;
+
+	fn apply_force(&mut self, body: Self::Body, force: Position, point: Position);
+	fn apply_torque(&mut self, body: Self::Body, torque: f32);
+	fn apply_linear_impulse(&mut self, body: Self::Body, impulse: Position, point: Position);
+	fn apply_angular_impulse(&mut self, body: Self::Body, impulse: f32);
+	fn set_transform(&mut self, body: Self::Body, position: Position, angle: f32);
+
+	/// Advances by one step, forces are cleared afterwards
+	fn step(&mut self, dt: f32, velocity_iterations: i32, position_iterations: i32);
+	/// Contacts begun, solved and ended since the last call
+	fn drain_contacts(&mut self) -> ContactLog;
+
+	/// Keys of the fixtures overlapping an area
+	fn query_area(&self, area: Rect) -> Vec<agent::Key>;
+	/// Key of a fixture containing a point, if any
+	fn query_point(&self, point: Position) -> Option<agent::Key>;
+}
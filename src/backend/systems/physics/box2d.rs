@@ -0,0 +1,286 @@
+use super::backend::*;
+use app::constants::*;
+use backend::world::agent;
+use cgmath::InnerSpace;
+use core::geometry::*;
+use std::cell::RefCell;
+use std::mem;
+use std::rc::Rc;
+use wrapped2d::b2;
+use wrapped2d::b2::Joint;
+use wrapped2d::dynamics::world::callbacks::ContactAccess;
+use wrapped2d::user_data::*;
+
+pub struct AgentData;
+
+impl UserDataTypes for AgentData {
+	type BodyData = agent::Key;
+	type JointData = JointLimit;
+	type FixtureData = agent::Key;
+}
+
+type ContactSet = Rc<RefCell<ContactLog>>;
+
+/// The native Box2D world, through wrapped2d
+pub struct Box2dBackend {
+	world: b2::World<AgentData>,
+	touched: ContactSet,
+}
+
+impl Default for Box2dBackend {
+	fn default() -> Self {
+		let touched = Rc::new(RefCell::new(ContactLog::default()));
+		Box2dBackend {
+			world: Self::new_world(touched.clone()),
+			touched,
+		}
+	}
+}
+
+impl Box2dBackend {
+	fn p2v(p: Position) -> b2::Vec2 { b2::Vec2 { x: p.x, y: p.y } }
+
+	fn v2p(p: b2::Vec2) -> Position { Position::new(p.x, p.y) }
+
+	fn new_world(touched: ContactSet) -> b2::World<AgentData> {
+		let mut world = b2::World::new(&b2::Vec2 { x: 0.0, y: -0.0 });
+		world.set_contact_listener(Box::new(ContactListener { touched }));
+		world
+	}
+}
+
+impl PhysicsBackend for Box2dBackend {
+	type Body = b2::BodyHandle;
+	type Joint = b2::JointHandle;
+
+	fn clear(&mut self) {
+		self.world = Self::new_world(self.touched.clone());
+		self.touched.borrow_mut().clear();
+	}
+
+	fn create_body(&mut self, def: &BodyDef) -> Self::Body {
+		let mut b_def = b2::BodyDef::new();
+		b_def.body_type = match def.body_type {
+			BodyType::Static => b2::BodyType::Static,
+			BodyType::Kinematic => b2::BodyType::Kinematic,
+			BodyType::Dynamic => b2::BodyType::Dynamic,
+		};
+		b_def.linear_damping = def.linear_damping;
+		b_def.angular_damping = def.angular_damping;
+		b_def.angle = def.transform.angle;
+		b_def.position = Self::p2v(def.transform.position);
+		b_def.linear_velocity = Self::p2v(def.motion.velocity);
+		b_def.angular_velocity = def.motion.spin;
+		self.world.create_body_with(&b_def, def.key)
+	}
+
+	fn destroy_body(&mut self, body: Self::Body) { self.world.destroy_body(body); }
+
+	fn add_fixture(&mut self, body: Self::Body, shape: &ShapeDef, def: &FixtureDef) {
+		let mut f_def = b2::FixtureDef::new();
+		f_def.density = def.density;
+		f_def.restitution = def.restitution;
+		f_def.friction = def.friction;
+		let mut body = self.world.body_mut(body);
+		match *shape {
+			ShapeDef::Circle { radius } => {
+				let mut circle_shape = b2::CircleShape::new();
+				circle_shape.set_radius(radius);
+				body.create_fixture_with(&circle_shape, &mut f_def, def.key);
+			}
+			ShapeDef::Polygon { ref vertices } => {
+				let mut poly = b2::PolygonShape::new();
+				poly.set(&vertices.iter().map(|p| Self::p2v(*p)).collect::<Vec<_>>());
+				body.create_fixture_with(&poly, &mut f_def, def.key);
+			}
+			ShapeDef::Loop { ref vertices } => {
+				let mut chain = b2::ChainShape::new();
+				chain.create_loop(&vertices.iter().map(|p| Self::p2v(*p)).collect::<Vec<_>>());
+				body.create_fixture_with(&chain, &mut f_def, def.key);
+			}
+		}
+	}
+
+	fn clear_fixtures(&mut self, body: Self::Body) {
+		let fixtures = self.world.body(body).fixtures().map(|(f, _)| f).collect::<Vec<_>>();
+		for fixture in fixtures {
+			self.world.body_mut(body).destroy_fixture(fixture);
+		}
+	}
+
+	fn create_joint(&mut self, def: &JointDef<Self::Body>) -> Self::Joint {
+		macro_rules! common_joint (
+			($joint:ident) => {
+				$joint.collide_connected = false;
+				$joint.reference_angle = def.reference_angle;
+				$joint.local_anchor_a = Self::p2v(def.anchor_a);
+				$joint.local_anchor_b = Self::p2v(def.anchor_b);
+				self.world.create_joint_with(&$joint, def.limit)
+			}
+		);
+		match def.kind {
+			JointKind::Revolute { lower_angle, upper_angle } => {
+				let mut joint = b2::RevoluteJointDef::new(def.body_a, def.body_b);
+				joint.enable_limit = true;
+				joint.upper_angle = upper_angle;
+				joint.lower_angle = lower_angle;
+				common_joint!(joint)
+			}
+			JointKind::Weld { frequency, damping_ratio } => {
+				let mut joint = b2::WeldJointDef::new(def.body_a, def.body_b);
+				joint.frequency = frequency;
+				joint.damping_ratio = damping_ratio;
+				common_joint!(joint)
+			}
+		}
+	}
+
+	fn destroy_joint(&mut self, joint: Self::Joint) { self.world.destroy_joint(joint); }
+
+	fn body_joints(&self, body: Self::Body) -> Vec<Self::Joint> {
+		self.world.body(body).joints().map(|(_, joint)| joint).collect()
+	}
+
+	fn joint_loads(&self, inv_dt: f32) -> Vec<(Self::Joint, JointLimit, f32)> {
+		self.world
+			.joints()
+			.map(|(h, j)| {
+				let joint = j.borrow();
+				let force = (*joint).reaction_force(inv_dt);
+				(h, *(*joint).user_data(), force.sqr_norm().sqrt())
+			}).collect()
+	}
+
+	fn bodies(&self) -> Vec<(Self::Body, BodyState)> {
+		self.world.bodies().map(|(h, _)| (h, self.body(h))).collect()
+	}
+
+	fn body(&self, body: Self::Body) -> BodyState {
+		let body = self.world.body(body);
+		let position = body.position();
+		let velocity = body.linear_velocity();
+		// Box2D measures inertia around the origin of the body
+		let local_center = Self::v2p(*body.local_center());
+		BodyState {
+			key: *body.user_data(),
+			transform: Transform::from_components(position.x, position.y, body.angle()),
+			motion: Motion::from_components(velocity.x, velocity.y, body.angular_velocity()),
+			center: Self::v2p(*body.world_center()),
+			mass: body.mass(),
+			inertia: body.inertia() - body.mass() * local_center.magnitude2(),
+		}
+	}
+
+	fn apply_force(&mut self, body: Self::Body, force: Position, point: Position) {
+		self.world.body_mut(body).apply_force(&Self::p2v(force), &Self::p2v(point), true);
+	}
+
+	fn apply_torque(&mut self, body: Self::Body, torque: f32) { self.world.body_mut(body).apply_torque(torque, true); }
+
+	fn apply_linear_impulse(&mut self, body: Self::Body, impulse: Position, point: Position) {
+		self.world.body_mut(body).apply_linear_impulse(&Self::p2v(impulse), &Self::p2v(point), true);
+	}
+
+	fn apply_angular_impulse(&mut self, body: Self::Body, impulse: f32) {
+		self.world.body_mut(body).apply_angular_impulse(impulse, true);
+	}
+
+	fn set_transform(&mut self, body: Self::Body, position: Position, angle: f32) {
+		self.world.body_mut(body).set_transform(&Self::p2v(position), angle);
+	}
+
+	fn step(&mut self, dt: f32, velocity_iterations: i32, position_iterations: i32) {
+		self.world.step(dt, velocity_iterations, position_iterations);
+	}
+
+	fn drain_contacts(&mut self) -> ContactLog { mem::replace(&mut *self.touched.borrow_mut(), ContactLog::default()) }
+
+	fn query_area(&self, area: Rect) -> Vec<agent::Key> {
+		let aabb = b2::AABB {
+			lower: Self::p2v(area.min),
+			upper: Self::p2v(area.max),
+		};
+		let mut result = Vec::new();
+		{
+			let mut callback = |body_h: b2::BodyHandle, fixture_h: b2::FixtureHandle| {
+				result.push(*self.world.body(body_h).fixture(fixture_h).user_data());
+				true
+			};
+			self.world.query_aabb(&mut callback, &aabb);
+		}
+		result
+	}
+
+	fn query_point(&self, pos: Position) -> Option<agent::Key> {
+		let point = Self::p2v(pos);
+		let eps = PICK_EPS;
+		let aabb = b2::AABB {
+			lower: b2::Vec2 {
+				x: pos.x - eps,
+				y: pos.y - eps,
+			},
+			upper: b2::Vec2 {
+				x: pos.x + eps,
+				y: pos.y + eps,
+			},
+		};
+		let mut result = None;
+		{
+			let mut callback = |body_h: b2::BodyHandle, fixture_h: b2::FixtureHandle| {
+				let body = self.world.body(body_h);
+				let fixture = body.fixture(fixture_h);
+				if fixture.test_point(&point) {
+					result = Some(*fixture.user_data());
+					false
+				} else {
+					true
+				}
+			};
+			self.world.query_aabb(&mut callback, &aabb);
+		}
+		result
+	}
+}
+
+struct ContactListener {
+	touched: ContactSet,
+}
+
+impl ContactListener {
+	/// Segment keys, contact point and normal from a to b
+	fn contact_of(ca: &ContactAccess<AgentData>) -> (agent::Key, agent::Key, Position, Position) {
+		let key_a = ca.fixture_a.user_data().no_bone();
+		let key_b = ca.fixture_b.user_data().no_bone();
+		let manifold = ca.contact.world_manifold();
+		let count = ca.contact.manifold().count.max(0) as usize;
+		let point = if count > 0 {
+			manifold.points[..count]
+				.iter()
+				.fold(Position::new(0., 0.), |sum, p| sum + Box2dBackend::v2p(*p))
+				/ count as f32
+		} else {
+			Box2dBackend::v2p(*ca.body_a.world_center())
+		};
+		(key_a, key_b, point, Box2dBackend::v2p(manifold.normal))
+	}
+}
+
+impl b2::ContactListener<AgentData> for ContactListener {
+	fn begin_contact(&mut self, ca: ContactAccess<AgentData>) {
+		let (segment, partner, point, normal) = Self::contact_of(&ca);
+		self.touched.borrow_mut().begin(segment, partner, point, normal);
+	}
+
+	fn end_contact(&mut self, ca: ContactAccess<AgentData>) {
+		let key_a = ca.fixture_a.user_data().no_bone();
+		let key_b = ca.fixture_b.user_data().no_bone();
+		self.touched.borrow_mut().end(key_a, key_b);
+	}
+
+	fn post_solve(&mut self, ca: ContactAccess<AgentData>, impulse: &b2::ContactImpulse) {
+		let (segment, partner, point, normal) = Self::contact_of(&ca);
+		let count = impulse.count.max(0) as usize;
+		let normal_impulse: f32 = impulse.normal_impulses[..count].iter().sum();
+		self.touched.borrow_mut().solve(segment, partner, point, normal, normal_impulse);
+	}
+}
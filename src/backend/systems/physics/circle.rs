@@ -0,0 +1,873 @@
+use super::backend::*;
+use backend::world::agent;
+use cgmath::InnerSpace;
+use core::geometry::*;
+use std::cmp::Ordering;
+use std::collections::HashSet;
+use std::f32::consts;
+use std::mem;
+
+const EPSILON: f32 = 1e-6;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct CircleBody(usize);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct CircleJoint(usize);
+
+fn cross(a: Position, b: Position) -> f32 { a.x * b.y - a.y * b.x }
+
+/// Vector storage with stable indices, iterated in index order so that runs are repeatable
+struct Slots<T> {
+	items: Vec<Option<T>>,
+	free: Vec<usize>,
+}
+
+impl<T> Default for Slots<T> {
+	fn default() -> Self {
+		Slots {
+			items: Vec::new(),
+			free: Vec::new(),
+		}
+	}
+}
+
+impl<T> Slots<T> {
+	fn insert(&mut self, item: T) -> usize {
+		match self.free.pop() {
+			Some(i) => {
+				self.items[i] = Some(item);
+				i
+			}
+			None => {
+				self.items.push(Some(item));
+				self.items.len() - 1
+			}
+		}
+	}
+
+	fn remove(&mut self, i: usize) -> Option<T> {
+		let item = self.items.get_mut(i).and_then(|slot| slot.take());
+		if item.is_some() {
+			self.free.push(i);
+		}
+		item
+	}
+
+	fn get(&self, i: usize) -> Option<&T> { self.items.get(i).and_then(|slot| slot.as_ref()) }
+
+	fn get_mut(&mut self, i: usize) -> Option<&mut T> { self.items.get_mut(i).and_then(|slot| slot.as_mut()) }
+
+	fn pair_mut(&mut self, i: usize, j: usize) -> Option<(&mut T, &mut T)> {
+		if i == j || i >= self.items.len() || j >= self.items.len() {
+			return None;
+		}
+		let (a, b) = if i < j {
+			let (low, high) = self.items.split_at_mut(j);
+			(&mut low[i], &mut high[0])
+		} else {
+			let (low, high) = self.items.split_at_mut(i);
+			(&mut high[0], &mut low[j])
+		};
+		match (a.as_mut(), b.as_mut()) {
+			(Some(a), Some(b)) => Some((a, b)),
+			_ => None,
+		}
+	}
+
+	fn clear(&mut self) {
+		self.items.clear();
+		self.free.clear();
+	}
+}
+
+/// Every fixture is approximated by the smallest circle around its centroid
+struct Circle {
+	key: agent::Key,
+	center: Position,
+	radius: f32,
+	mass: f32,
+}
+
+struct Body {
+	key: agent::Key,
+	body_type: BodyType,
+	/// origin of the body, as set by `set_transform`
+	position: Position,
+	angle: f32,
+	/// velocities of the center of mass
+	velocity: Position,
+	spin: f32,
+	force: Position,
+	torque: f32,
+	linear_damping: f32,
+	angular_damping: f32,
+	mass: f32,
+	inertia: f32,
+	local_center: Position,
+	circles: Vec<Circle>,
+	outlines: Vec<Box<[Position]>>,
+	last_center: Position,
+	last_angle: f32,
+}
+
+impl Body {
+	fn transform(&self) -> Transform { Transform::new(self.position, self.angle) }
+
+	fn world_point(&self, local: Position) -> Position { self.transform().apply(local) }
+
+	fn center(&self) -> Position { self.world_point(self.local_center) }
+
+	fn is_dynamic(&self) -> bool { self.body_type == BodyType::Dynamic }
+
+	fn inv_mass(&self) -> f32 {
+		if self.is_dynamic() && self.mass > 0. {
+			1. / self.mass
+		} else {
+			0.
+		}
+	}
+
+	fn inv_inertia(&self) -> f32 {
+		if self.is_dynamic() && self.inertia > 0. {
+			1. / self.inertia
+		} else {
+			0.
+		}
+	}
+
+	/// Places the center of mass, the origin follows
+	fn move_center(&mut self, center: Position, angle: f32) {
+		self.angle = angle;
+		self.position = center - Transform::from_angle(angle).apply_rotation(self.local_center);
+	}
+
+	fn displace(&mut self, offset: Position, rotation: f32) {
+		let center = self.center();
+		let angle = self.angle;
+		self.move_center(center + offset, angle + rotation);
+	}
+
+	/// Like Box2D, a dynamic body without fixtures weighs one unit, and keeps its origin when its mass changes
+	fn update_mass(&mut self) {
+		let mass: f32 = self.circles.iter().map(|c| c.mass).sum();
+		if mass > 0. {
+			let local_center = self.circles.iter().fold(Position::new(0., 0.), |sum, c| sum + c.center * c.mass) / mass;
+			self.local_center = local_center;
+			self.inertia = self
+				.circles
+				.iter()
+				.map(|c| c.mass * (c.radius * c.radius * 0.5 + (c.center - local_center).magnitude2()))
+				.sum();
+			self.mass = mass;
+		} else {
+			self.local_center = Position::new(0., 0.);
+			self.inertia = 0.;
+			self.mass = 1.;
+		}
+	}
+
+	/// Semi-implicit Euler, with the same damping formula as Box2D
+	fn integrate(&mut self, dt: f32) {
+		let center = self.center();
+		self.last_center = center;
+		self.last_angle = self.angle;
+		match self.body_type {
+			BodyType::Dynamic => {
+				let (inv_mass, inv_inertia) = (self.inv_mass(), self.inv_inertia());
+				self.velocity += self.force * (inv_mass * dt);
+				self.spin += self.torque * inv_inertia * dt;
+				self.velocity *= 1. / (1. + dt * self.linear_damping);
+				self.spin *= 1. / (1. + dt * self.angular_damping);
+			}
+			BodyType::Kinematic => {}
+			BodyType::Static => {
+				self.velocity = Position::new(0., 0.);
+				self.spin = 0.;
+			}
+		}
+		self.force = Position::new(0., 0.);
+		self.torque = 0.;
+		let (velocity, spin, angle) = (self.velocity, self.spin, self.angle);
+		self.move_center(center + velocity * dt, angle + spin * dt);
+	}
+
+	/// Velocities follow the positions left by the constraint solver
+	fn settle(&mut self, dt: f32) {
+		if self.is_dynamic() {
+			self.velocity = (self.center() - self.last_center) / dt;
+			self.spin = (self.angle - self.last_angle) / dt;
+		}
+	}
+
+	fn state(&self) -> BodyState {
+		BodyState {
+			key: self.key,
+			transform: self.transform(),
+			motion: Motion::new(self.velocity, self.spin),
+			center: self.center(),
+			mass: if self.is_dynamic() { self.mass } else { 0. },
+			inertia: if self.is_dynamic() { self.inertia } else { 0. },
+		}
+	}
+}
+
+#[derive(Clone, Copy)]
+struct Joint {
+	def: JointDef<CircleBody>,
+	/// mass weighted distance the joint has pulled its anchors together during the last step
+	correction: f32,
+}
+
+/// Two overlapping circles, as body and circle indices
+struct Pair {
+	a: usize,
+	circle_a: usize,
+	b: usize,
+	circle_b: usize,
+}
+
+struct Proxy {
+	body: usize,
+	circle: usize,
+	agent_id: usize,
+	is_dynamic: bool,
+	center: Position,
+	radius: f32,
+}
+
+/// A lightweight backend in plain Rust, with no native dependencies and repeatable results.
+/// Fixtures are approximated by circles, constraints are solved on positions, and restitution
+/// and friction are ignored; welds are rigid. Segments of the same agent don't collide,
+/// and outlines push bodies back without reporting contacts
+#[derive(Default)]
+pub struct CircleBackend {
+	bodies: Slots<Body>,
+	joints: Slots<Joint>,
+	touching: Vec<(agent::Key, agent::Key)>,
+	contacts: ContactLog,
+}
+
+impl CircleBackend {
+	fn body_ref(&self, body: CircleBody) -> &Body { self.bodies.get(body.0).expect("Invalid body handle") }
+
+	fn body_mut(&mut self, body: CircleBody) -> &mut Body { self.bodies.get_mut(body.0).expect("Invalid body handle") }
+
+	fn find_pairs(&self) -> Vec<Pair> {
+		let jointed = self
+			.joints
+			.items
+			.iter()
+			.filter_map(|j| j.as_ref())
+			.map(|j| {
+				let (a, b) = (j.def.body_a.0, j.def.body_b.0);
+				(a.min(b), a.max(b))
+			}).collect::<HashSet<_>>();
+		let mut proxies = Vec::new();
+		for (i, body) in self.bodies.items.iter().enumerate() {
+			if let Some(ref body) = *body {
+				for (c, circle) in body.circles.iter().enumerate() {
+					proxies.push(Proxy {
+						body: i,
+						circle: c,
+						agent_id: body.key.agent_id,
+						is_dynamic: body.is_dynamic(),
+						center: body.world_point(circle.center),
+						radius: circle.radius,
+					});
+				}
+			}
+		}
+		// sweep and prune along the x axis
+		proxies.sort_by(|p, q| (p.center.x - p.radius).partial_cmp(&(q.center.x - q.radius)).unwrap_or(Ordering::Equal));
+		let mut pairs = Vec::new();
+		for (n, p) in proxies.iter().enumerate() {
+			for q in &proxies[n + 1..] {
+				if q.center.x - q.radius > p.center.x + p.radius {
+					break;
+				}
+				if p.body == q.body
+					|| p.agent_id == q.agent_id
+					|| !(p.is_dynamic || q.is_dynamic)
+					|| jointed.contains(&(p.body.min(q.body), p.body.max(q.body)))
+				{
+					continue;
+				}
+				let reach = p.radius + q.radius;
+				if (q.center - p.center).magnitude2() < reach * reach {
+					pairs.push(Pair {
+						a: p.body,
+						circle_a: p.circle,
+						b: q.body,
+						circle_b: q.circle,
+					});
+				}
+			}
+		}
+		pairs
+	}
+
+	/// Pulls the anchors together, then brings the relative angle back within its limits.
+	/// Returns the mass weighted distance corrected
+	fn solve_joint(def: &JointDef<CircleBody>, a: &mut Body, b: &mut Body) -> f32 {
+		let pa = a.world_point(def.anchor_a);
+		let pb = b.world_point(def.anchor_b);
+		let ra = pa - a.center();
+		let rb = pb - b.center();
+		let delta = pb - pa;
+		let distance = delta.magnitude();
+		let mut lambda = 0.;
+		if distance > EPSILON {
+			let n = delta / distance;
+			let wa = a.inv_mass() + a.inv_inertia() * cross(ra, n).powi(2);
+			let wb = b.inv_mass() + b.inv_inertia() * cross(rb, n).powi(2);
+			if wa + wb > 0. {
+				lambda = distance / (wa + wb);
+				let (inv_mass_a, inv_inertia_a) = (a.inv_mass(), a.inv_inertia());
+				let (inv_mass_b, inv_inertia_b) = (b.inv_mass(), b.inv_inertia());
+				a.displace(n * (lambda * inv_mass_a), inv_inertia_a * cross(ra, n * lambda));
+				b.displace(n * (-lambda * inv_mass_b), -inv_inertia_b * cross(rb, n * lambda));
+			}
+		}
+		let relative = b.angle - a.angle - def.reference_angle;
+		let error = match def.kind {
+			JointKind::Weld { .. } => relative,
+			JointKind::Revolute { lower_angle, upper_angle } => {
+				if relative < lower_angle {
+					relative - lower_angle
+				} else if relative > upper_angle {
+					relative - upper_angle
+				} else {
+					0.
+				}
+			}
+		};
+		let w = a.inv_inertia() + b.inv_inertia();
+		if error != 0. && w > 0. {
+			let l = error / w;
+			let (inv_inertia_a, inv_inertia_b) = (a.inv_inertia(), b.inv_inertia());
+			a.displace(Position::new(0., 0.), inv_inertia_a * l);
+			b.displace(Position::new(0., 0.), -inv_inertia_b * l);
+		}
+		lambda
+	}
+
+	fn solve_joints(&mut self) {
+		for j in 0..self.joints.items.len() {
+			let def = match self.joints.get(j) {
+				Some(joint) => joint.def,
+				None => continue,
+			};
+			let correction = match self.bodies.pair_mut(def.body_a.0, def.body_b.0) {
+				Some((a, b)) => Self::solve_joint(&def, a, b),
+				None => continue,
+			};
+			if let Some(joint) = self.joints.get_mut(j) {
+				joint.correction += correction;
+			}
+		}
+	}
+
+	/// Pushes overlapping circles apart, returns the mass weighted distance corrected
+	fn solve_contact(&mut self, pair: &Pair) -> f32 {
+		match self.bodies.pair_mut(pair.a, pair.b) {
+			Some((a, b)) => {
+				let pa = a.world_point(a.circles[pair.circle_a].center);
+				let pb = b.world_point(b.circles[pair.circle_b].center);
+				let delta = pb - pa;
+				let distance = delta.magnitude();
+				let depth = a.circles[pair.circle_a].radius + b.circles[pair.circle_b].radius - distance;
+				let (wa, wb) = (a.inv_mass(), b.inv_mass());
+				if depth <= 0. || wa + wb <= 0. {
+					return 0.;
+				}
+				let n = if distance > EPSILON { delta / distance } else { Position::unit_x() };
+				let lambda = depth / (wa + wb);
+				a.displace(n * (-lambda * wa), 0.);
+				b.displace(n * (lambda * wb), 0.);
+				lambda
+			}
+			None => 0.,
+		}
+	}
+
+	/// Keeps dynamic circles on the side of the outline edges they started from
+	fn solve_outlines(&mut self) {
+		let mut edges = Vec::new();
+		for body in self.bodies.items.iter().filter_map(|b| b.as_ref()) {
+			for outline in &body.outlines {
+				let n = outline.len();
+				for i in 0..n {
+					edges.push((body.world_point(outline[i]), body.world_point(outline[(i + 1) % n])));
+				}
+			}
+		}
+		if edges.is_empty() {
+			return;
+		}
+		for body in self.bodies.items.iter_mut().filter_map(|b| b.as_mut()) {
+			if !body.is_dynamic() {
+				continue;
+			}
+			for c in 0..body.circles.len() {
+				for &(v0, v1) in &edges {
+					let p = body.world_point(body.circles[c].center);
+					let radius = body.circles[c].radius;
+					let edge = v1 - v0;
+					let length2 = edge.magnitude2();
+					let t = if length2 > EPSILON { ((p - v0).dot(edge) / length2).max(0.).min(1.) } else { 0. };
+					let delta = p - (v0 + edge * t);
+					let distance = delta.magnitude();
+					if distance < radius && distance > EPSILON {
+						body.displace(delta * ((radius - distance) / distance), 0.);
+					}
+				}
+			}
+		}
+	}
+
+	fn record_contacts(&mut self, pairs: &[Pair], corrections: &[f32], dt: f32) {
+		let previous = mem::replace(&mut self.touching, Vec::new());
+		let was_touching = previous.iter().cloned().collect::<HashSet<_>>();
+		let mut seen = HashSet::new();
+		for (pair, correction) in pairs.iter().zip(corrections) {
+			let (key_a, key_b, point, normal) = {
+				let (a, b) = (self.body_ref(CircleBody(pair.a)), self.body_ref(CircleBody(pair.b)));
+				let (ca, cb) = (&a.circles[pair.circle_a], &b.circles[pair.circle_b]);
+				let pa = a.world_point(ca.center);
+				let delta = b.world_point(cb.center) - pa;
+				let distance = delta.magnitude();
+				let normal = if distance > EPSILON { delta / distance } else { Position::unit_x() };
+				(ca.key.no_bone(), cb.key.no_bone(), pa + normal * ca.radius, normal)
+			};
+			if !seen.contains(&(key_a, key_b)) && !seen.contains(&(key_b, key_a)) {
+				if !was_touching.contains(&(key_a, key_b)) && !was_touching.contains(&(key_b, key_a)) {
+					self.contacts.begin(key_a, key_b, point, normal);
+				}
+				seen.insert((key_a, key_b));
+				self.touching.push((key_a, key_b));
+			}
+			self.contacts.solve(key_a, key_b, point, normal, correction / dt);
+		}
+		for (key_a, key_b) in previous {
+			if !seen.contains(&(key_a, key_b)) && !seen.contains(&(key_b, key_a)) {
+				self.contacts.end(key_a, key_b);
+			}
+		}
+	}
+}
+
+impl PhysicsBackend for CircleBackend {
+	type Body = CircleBody;
+	type Joint = CircleJoint;
+
+	fn clear(&mut self) {
+		self.bodies.clear();
+		self.joints.clear();
+		self.touching.clear();
+		self.contacts.clear();
+	}
+
+	fn create_body(&mut self, def: &BodyDef) -> Self::Body {
+		let position = def.transform.position;
+		CircleBody(self.bodies.insert(Body {
+			key: def.key,
+			body_type: def.body_type,
+			position,
+			angle: def.transform.angle,
+			velocity: def.motion.velocity,
+			spin: def.motion.spin,
+			force: Position::new(0., 0.),
+			torque: 0.,
+			linear_damping: def.linear_damping,
+			angular_damping: def.angular_damping,
+			mass: 1.,
+			inertia: 0.,
+			local_center: Position::new(0., 0.),
+			circles: Vec::new(),
+			outlines: Vec::new(),
+			last_center: position,
+			last_angle: def.transform.angle,
+		}))
+	}
+
+	fn destroy_body(&mut self, body: Self::Body) {
+		for joint in self.body_joints(body) {
+			self.destroy_joint(joint);
+		}
+		if let Some(removed) = self.bodies.remove(body.0) {
+			let keys = removed.circles.iter().map(|c| c.key.no_bone()).collect::<HashSet<_>>();
+			let touching = mem::replace(&mut self.touching, Vec::new());
+			for (key_a, key_b) in touching {
+				if keys.contains(&key_a) || keys.contains(&key_b) {
+					self.contacts.end(key_a, key_b);
+				} else {
+					self.touching.push((key_a, key_b));
+				}
+			}
+		}
+	}
+
+	fn add_fixture(&mut self, body: Self::Body, shape: &ShapeDef, def: &FixtureDef) {
+		let body = self.body_mut(body);
+		match *shape {
+			ShapeDef::Circle { radius } => body.circles.push(Circle {
+				key: def.key,
+				center: Position::new(0., 0.),
+				radius,
+				mass: def.density * consts::PI * radius * radius,
+			}),
+			ShapeDef::Polygon { ref vertices } => {
+				let n = vertices.len().max(1) as f32;
+				let center = vertices.iter().fold(Position::new(0., 0.), |sum, v| sum + *v) / n;
+				let radius = vertices.iter().map(|v| (*v - center).magnitude()).fold(0., f32::max);
+				let area = (0..vertices.len())
+					.map(|i| cross(vertices[i], vertices[(i + 1) % vertices.len()]))
+					.sum::<f32>()
+					.abs() * 0.5;
+				body.circles.push(Circle {
+					key: def.key,
+					center,
+					radius,
+					mass: def.density * area,
+				})
+			}
+			ShapeDef::Loop { ref vertices } => body.outlines.push(vertices.clone()),
+		}
+		body.update_mass();
+	}
+
+	fn clear_fixtures(&mut self, body: Self::Body) {
+		let body = self.body_mut(body);
+		body.circles.clear();
+		body.outlines.clear();
+		body.update_mass();
+	}
+
+	fn create_joint(&mut self, def: &JointDef<Self::Body>) -> Self::Joint {
+		CircleJoint(self.joints.insert(Joint {
+			def: *def,
+			correction: 0.,
+		}))
+	}
+
+	fn destroy_joint(&mut self, joint: Self::Joint) { self.joints.remove(joint.0); }
+
+	fn body_joints(&self, body: Self::Body) -> Vec<Self::Joint> {
+		self.joints
+			.items
+			.iter()
+			.enumerate()
+			.filter_map(|(i, j)| match *j {
+				Some(ref j) if j.def.body_a == body || j.def.body_b == body => Some(CircleJoint(i)),
+				_ => None,
+			}).collect()
+	}
+
+	/// The correction of a joint over a step is a mass times a distance, hence the squared inverse time
+	fn joint_loads(&self, inv_dt: f32) -> Vec<(Self::Joint, JointLimit, f32)> {
+		self.joints
+			.items
+			.iter()
+			.enumerate()
+			.filter_map(|(i, j)| {
+				j.as_ref()
+					.map(|j| (CircleJoint(i), j.def.limit, j.correction * inv_dt * inv_dt))
+			}).collect()
+	}
+
+	fn bodies(&self) -> Vec<(Self::Body, BodyState)> {
+		self.bodies
+			.items
+			.iter()
+			.enumerate()
+			.filter_map(|(i, b)| b.as_ref().map(|b| (CircleBody(i), b.state())))
+			.collect()
+	}
+
+	fn body(&self, body: Self::Body) -> BodyState { self.body_ref(body).state() }
+
+	fn apply_force(&mut self, body: Self::Body, force: Position, point: Position) {
+		let body = self.body_mut(body);
+		let r = point - body.center();
+		body.force += force;
+		body.torque += cross(r, force);
+	}
+
+	fn apply_torque(&mut self, body: Self::Body, torque: f32) { self.body_mut(body).torque += torque; }
+
+	fn apply_linear_impulse(&mut self, body: Self::Body, impulse: Position, point: Position) {
+		let body = self.body_mut(body);
+		let r = point - body.center();
+		let (inv_mass, inv_inertia) = (body.inv_mass(), body.inv_inertia());
+		body.velocity += impulse * inv_mass;
+		body.spin += inv_inertia * cross(r, impulse);
+	}
+
+	fn apply_angular_impulse(&mut self, body: Self::Body, impulse: f32) {
+		let body = self.body_mut(body);
+		let inv_inertia = body.inv_inertia();
+		body.spin += inv_inertia * impulse;
+	}
+
+	fn set_transform(&mut self, body: Self::Body, position: Position, angle: f32) {
+		let body = self.body_mut(body);
+		body.position = position;
+		body.angle = angle;
+	}
+
+	fn step(&mut self, dt: f32, _velocity_iterations: i32, position_iterations: i32) {
+		if dt <= 0. {
+			return;
+		}
+		for body in self.bodies.items.iter_mut().filter_map(|b| b.as_mut()) {
+			body.integrate(dt);
+		}
+		for joint in self.joints.items.iter_mut().filter_map(|j| j.as_mut()) {
+			joint.correction = 0.;
+		}
+		let pairs = self.find_pairs();
+		let mut corrections = vec![0.; pairs.len()];
+		for _ in 0..position_iterations.max(1) {
+			self.solve_joints();
+			for (pair, correction) in pairs.iter().zip(corrections.iter_mut()) {
+				*correction += self.solve_contact(pair);
+			}
+			self.solve_outlines();
+		}
+		for body in self.bodies.items.iter_mut().filter_map(|b| b.as_mut()) {
+			body.settle(dt);
+		}
+		self.record_contacts(&pairs, &corrections, dt);
+	}
+
+	fn drain_contacts(&mut self) -> ContactLog { mem::replace(&mut self.contacts, ContactLog::default()) }
+
+	fn query_area(&self, area: Rect) -> Vec<agent::Key> {
+		let mut result = Vec::new();
+		for body in self.bodies.items.iter().filter_map(|b| b.as_ref()) {
+			for circle in &body.circles {
+				let p = body.world_point(circle.center);
+				let r = circle.radius;
+				if p.x + r >= area.min.x && p.x - r <= area.max.x && p.y + r >= area.min.y && p.y - r <= area.max.y {
+					result.push(circle.key);
+				}
+			}
+		}
+		result
+	}
+
+	fn query_point(&self, point: Position) -> Option<agent::Key> {
+		for body in self.bodies.items.iter().filter_map(|b| b.as_ref()) {
+			for circle in &body.circles {
+				if (body.world_point(circle.center) - point).magnitude2() <= circle.radius * circle.radius {
+					return Some(circle.key);
+				}
+			}
+		}
+		None
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use backend::world::contact::ContactEvent;
+
+	const DT: f32 = 1. / 60.;
+	const ITERATIONS: i32 = 8;
+
+	fn body_def(agent_id: usize, x: f32, y: f32) -> BodyDef {
+		BodyDef {
+			key: agent::Key::with_segment(agent_id, 0),
+			body_type: BodyType::Dynamic,
+			transform: Transform::new(Position::new(x, y), 0.),
+			motion: Motion::new(Position::new(0., 0.), 0.),
+			linear_damping: 0.,
+			angular_damping: 0.,
+		}
+	}
+
+	fn add_ball(backend: &mut CircleBackend, agent_id: usize, x: f32, y: f32, radius: f32) -> CircleBody {
+		let def = body_def(agent_id, x, y);
+		let body = backend.create_body(&def);
+		backend.add_fixture(
+			body,
+			&ShapeDef::Circle { radius },
+			&FixtureDef {
+				key: def.key,
+				density: 1.,
+				restitution: 0.,
+				friction: 0.,
+			},
+		);
+		body
+	}
+
+	fn scene() -> (CircleBackend, Vec<CircleBody>) {
+		let mut backend = CircleBackend::default();
+		let bodies = (0..10)
+			.map(|i| add_ball(&mut backend, i + 1, i as f32 * 1.5, (i % 3) as f32, 1.))
+			.collect::<Vec<_>>();
+		for (i, &body) in bodies.iter().enumerate() {
+			backend.apply_linear_impulse(body, Position::new(-(i as f32), i as f32 * 0.5), Position::new(0., 0.));
+		}
+		(backend, bodies)
+	}
+
+	#[test]
+	fn steps_are_repeatable() {
+		let (mut first, bodies) = scene();
+		let (mut second, _) = scene();
+		for _ in 0..120 {
+			first.step(DT, ITERATIONS, ITERATIONS);
+			second.step(DT, ITERATIONS, ITERATIONS);
+		}
+		for &body in &bodies {
+			let (a, b) = (first.body(body), second.body(body));
+			assert_eq!(a.transform.position, b.transform.position);
+			assert_eq!(a.transform.angle, b.transform.angle);
+			assert_eq!(a.motion.velocity, b.motion.velocity);
+		}
+	}
+
+	#[test]
+	fn bodies_move_at_constant_velocity() {
+		let mut backend = CircleBackend::default();
+		let body = add_ball(&mut backend, 1, 0., 0., 1.);
+		let mass = backend.body(body).mass;
+		backend.apply_linear_impulse(body, Position::new(mass, 0.), Position::new(0., 0.));
+		for _ in 0..60 {
+			backend.step(DT, ITERATIONS, ITERATIONS);
+		}
+		let state = backend.body(body);
+		assert!((state.transform.position.x - 1.).abs() < 1e-3);
+		assert!((state.motion.velocity.x - 1.).abs() < 1e-3);
+	}
+
+	#[test]
+	fn overlapping_bodies_are_pushed_apart_and_report_contacts() {
+		let mut backend = CircleBackend::default();
+		let a = add_ball(&mut backend, 1, 0., 0., 1.);
+		let b = add_ball(&mut backend, 2, 1.5, 0., 1.);
+		backend.step(DT, ITERATIONS, ITERATIONS);
+
+		let distance = (backend.body(b).center - backend.body(a).center).magnitude();
+		assert!(distance >= 2. - 1e-3);
+		let contacts = backend.drain_contacts();
+		let begins = contacts
+			.events
+			.iter()
+			.filter(|e| match **e {
+				ContactEvent::Begin(_) => true,
+				_ => false,
+			}).count();
+		assert_eq!(begins, 2);
+		assert!(contacts.impulses.values().all(|c| c.impulse > 0.));
+
+		// separated, the contact ends
+		backend.apply_linear_impulse(b, Position::new(100., 0.), Position::new(0., 0.));
+		backend.step(DT, ITERATIONS, ITERATIONS);
+		let ends = backend
+			.drain_contacts()
+			.events
+			.iter()
+			.filter(|e| match **e {
+				ContactEvent::End(_, _) => true,
+				_ => false,
+			}).count();
+		assert_eq!(ends, 2);
+	}
+
+	#[test]
+	fn segments_of_the_same_agent_do_not_collide() {
+		let mut backend = CircleBackend::default();
+		let a = add_ball(&mut backend, 1, 0., 0., 1.);
+		let b = add_ball(&mut backend, 1, 1.5, 0., 1.);
+		backend.step(DT, ITERATIONS, ITERATIONS);
+		let distance = (backend.body(b).center - backend.body(a).center).magnitude();
+		assert!((distance - 1.5).abs() < 1e-3);
+		assert!(backend.drain_contacts().events.is_empty());
+	}
+
+	fn joint(kind: JointKind, a: CircleBody, b: CircleBody) -> JointDef<CircleBody> {
+		JointDef {
+			kind,
+			body_a: a,
+			body_b: b,
+			anchor_a: Position::new(1., 0.),
+			anchor_b: Position::new(-1., 0.),
+			reference_angle: 0.,
+			limit: JointLimit {
+				distal: agent::Key::with_segment(1, 1),
+				strength: 1e6,
+			},
+		}
+	}
+
+	#[test]
+	fn revolute_joints_hold_anchors_and_angle_limits() {
+		let mut backend = CircleBackend::default();
+		let a = add_ball(&mut backend, 1, 0., 0., 1.);
+		let b = add_ball(&mut backend, 1, 2., 0., 1.);
+		let limit = 0.25;
+		backend.create_joint(&joint(
+			JointKind::Revolute {
+				lower_angle: -limit,
+				upper_angle: limit,
+			},
+			a,
+			b,
+		));
+		backend.apply_linear_impulse(b, Position::new(0., 10.), Position::new(0., 0.));
+		backend.apply_angular_impulse(b, 10.);
+		for _ in 0..60 {
+			backend.step(DT, ITERATIONS, ITERATIONS);
+		}
+		let (sa, sb) = (backend.body(a), backend.body(b));
+		let gap = sb.world_point(Position::new(-1., 0.)) - sa.world_point(Position::new(1., 0.));
+		assert!(gap.magnitude() < 0.05);
+		let relative = sb.transform.angle - sa.transform.angle;
+		assert!(relative.abs() <= limit + 0.05);
+	}
+
+	#[test]
+	fn welds_are_rigid() {
+		let mut backend = CircleBackend::default();
+		let a = add_ball(&mut backend, 1, 0., 0., 1.);
+		let b = add_ball(&mut backend, 1, 2., 0., 1.);
+		backend.create_joint(&joint(
+			JointKind::Weld {
+				frequency: 0.,
+				damping_ratio: 0.,
+			},
+			a,
+			b,
+		));
+		backend.apply_angular_impulse(b, 10.);
+		for _ in 0..60 {
+			backend.step(DT, ITERATIONS, ITERATIONS);
+		}
+		let (sa, sb) = (backend.body(a), backend.body(b));
+		assert!((sb.transform.angle - sa.transform.angle).abs() < 0.05);
+		assert_eq!(backend.body_joints(a), backend.body_joints(b));
+	}
+
+	#[test]
+	fn destroying_a_body_removes_its_joints() {
+		let mut backend = CircleBackend::default();
+		let a = add_ball(&mut backend, 1, 0., 0., 1.);
+		let b = add_ball(&mut backend, 1, 2., 0., 1.);
+		backend.create_joint(&joint(
+			JointKind::Weld {
+				frequency: 0.,
+				damping_ratio: 0.,
+			},
+			a,
+			b,
+		));
+		backend.destroy_body(b);
+		assert!(backend.body_joints(a).is_empty());
+		assert_eq!(backend.bodies().len(), 1);
+	}
+}
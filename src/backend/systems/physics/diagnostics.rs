@@ -0,0 +1,111 @@
+use super::backend::BodyState;
+use backend::obj::Id;
+use cgmath::InnerSpace;
+use core::geometry::*;
+use std::collections::BTreeSet;
+
+/// Totals over all the bodies after a physics update, and the agents which have blown up
+#[derive(Clone, Debug, Default)]
+pub struct Diagnostics {
+	pub bodies: usize,
+	pub kinetic_energy: f32,
+	pub momentum: Position,
+	/// around the origin of the world
+	pub angular_momentum: f32,
+	/// agents with any body in a non-finite state, or moving faster than the limits
+	pub unstable: BTreeSet<Id>,
+}
+
+fn is_finite(body: &BodyState) -> bool {
+	let values = [
+		body.transform.position.x,
+		body.transform.position.y,
+		body.transform.angle,
+		body.motion.velocity.x,
+		body.motion.velocity.y,
+		body.motion.spin,
+		body.center.x,
+		body.center.y,
+	];
+	values.iter().all(|v| v.is_finite())
+}
+
+impl Diagnostics {
+	/// Unstable bodies are left out of the totals, so they stay meaningful
+	pub fn measure<'a, I>(bodies: I, max_speed: f32, max_spin: f32) -> Self
+	where I: IntoIterator<Item = &'a BodyState> {
+		let mut diagnostics = Diagnostics::default();
+		for body in bodies {
+			diagnostics.bodies += 1;
+			let velocity = body.motion.velocity;
+			let spin = body.motion.spin;
+			if !is_finite(body) || velocity.magnitude2() > max_speed * max_speed || spin.abs() > max_spin {
+				diagnostics.unstable.insert(body.key.agent_id);
+				continue;
+			}
+			let momentum = velocity * body.mass;
+			diagnostics.kinetic_energy += 0.5 * (body.mass * velocity.magnitude2() + body.inertia * spin * spin);
+			diagnostics.momentum += momentum;
+			diagnostics.angular_momentum +=
+				body.inertia * spin + body.center.x * momentum.y - body.center.y * momentum.x;
+		}
+		diagnostics
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use backend::world::agent;
+	use std::f32;
+
+	const MAX_SPEED: f32 = 10.;
+	const MAX_SPIN: f32 = 10.;
+
+	fn body(agent_id: Id, mass: f32, inertia: f32, motion: Motion, center: Position) -> BodyState {
+		BodyState {
+			key: agent::Key::with_id(agent_id),
+			transform: Transform::new(center, 0.),
+			motion,
+			center,
+			mass,
+			inertia,
+		}
+	}
+
+	fn stable_bodies() -> Vec<BodyState> {
+		vec![
+			body(1, 2., 1., Motion::from_components(3., 0., 2.), Position::new(0., 1.)),
+			body(2, 1., 0.5, Motion::from_components(0., -2., 0.), Position::new(2., 0.)),
+		]
+	}
+
+	#[test]
+	fn totals_add_up_over_all_the_bodies() {
+		let diagnostics = Diagnostics::measure(&stable_bodies(), MAX_SPEED, MAX_SPIN);
+		assert_eq!(2, diagnostics.bodies);
+		assert_eq!(13., diagnostics.kinetic_energy);
+		assert_eq!(Position::new(6., -2.), diagnostics.momentum);
+		assert_eq!(-8., diagnostics.angular_momentum);
+		assert!(diagnostics.unstable.is_empty());
+	}
+
+	#[test]
+	fn unstable_bodies_are_reported_and_left_out_of_the_totals() {
+		let mut bodies = stable_bodies();
+		let mut broken = body(3, 1., 1., Motion::default(), Position::new(0., 0.));
+		broken.transform.position.x = f32::NAN;
+		bodies.push(broken);
+		bodies.push(body(4, 1., 1., Motion::from_components(MAX_SPEED, 1., 0.), Position::new(0., 0.)));
+		bodies.push(body(5, 1., 1., Motion::from_components(0., 0., -2. * MAX_SPIN), Position::new(0., 0.)));
+		// right at the limits is still fine
+		bodies.push(body(6, 0., 0., Motion::from_components(0., MAX_SPEED, MAX_SPIN), Position::new(0., 0.)));
+
+		let diagnostics = Diagnostics::measure(&bodies, MAX_SPEED, MAX_SPIN);
+		assert_eq!(6, diagnostics.bodies);
+		assert_eq!(vec![3, 4, 5], diagnostics.unstable.iter().cloned().collect::<Vec<_>>());
+		assert_eq!(13., diagnostics.kinetic_energy);
+		assert_eq!(Position::new(6., -2.), diagnostics.momentum);
+		assert_eq!(-8., diagnostics.angular_momentum);
+	}
+}
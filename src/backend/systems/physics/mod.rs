@@ -0,0 +1,899 @@
+pub mod backend;
+#[cfg(feature = "box2d")]
+pub mod box2d;
+#[cfg_attr(feature = "box2d", allow(dead_code))]
+pub mod circle;
+pub mod diagnostics;
+
+pub use self::backend::PhysicsBackend;
+#[cfg(feature = "box2d")]
+pub use self::box2d::Box2dBackend;
+pub use self::circle::CircleBackend;
+pub use self::diagnostics::Diagnostics;
+
+use self::backend::*;
+use super::*;
+use app::constants::*;
+use app::Event;
+use backend::messagebus::{Inbox, Message, PubSub, ReceiveDrain, Whiteboard};
+use backend::obj;
+use backend::obj::*;
+use backend::world;
+use backend::world::agent;
+use backend::world::agent::TypedAgent;
+use backend::world::contact::ContactEvent;
+use backend::world::scenario::{DiagnosticsMode, PhysicsSettings};
+use backend::world::segment;
+use backend::world::segment::Intent;
+use backend::world::segment::PilotRotation;
+use backend::world::topology::Topology;
+use cgmath::InnerSpace;
+use core::geometry::Transform;
+use core::geometry::*;
+use std::collections::HashMap;
+use std::collections::HashSet;
+
+/// Box2D when available, the pure Rust backend otherwise
+#[cfg(feature = "box2d")]
+pub type DefaultBackend = Box2dBackend;
+#[cfg(not(feature = "box2d"))]
+pub type DefaultBackend = CircleBackend;
+
+const EXTENT_ID: Id = 0xFFFF_FFFF;
+
+/// One side of a segment outline, pushing against the surrounding fluid.
+/// Midpoint and normal are in body coordinates
+struct Panel {
+	midpoint: Position,
+	normal: Position,
+	area: f32,
+}
+
+pub struct PhysicsSystem<B: PhysicsBackend = DefaultBackend> {
+	backend: B,
+	topology: Topology,
+	extent_handle: Option<B::Body>,
+	settings: PhysicsSettings,
+	accumulator: f32,
+	inbox: Option<Inbox>,
+	handles: HashMap<agent::Key, B::Body>,
+	panels: HashMap<B::Body, Box<[Panel]>>,
+	contacts: ContactLog,
+	severed: Vec<agent::Key>,
+	quarantined: Vec<Id>,
+	picked: HashSet<Id>,
+}
+
+#[allow(unused)]
+enum BodyUpdate {
+	Transform(Position, f32),
+	Torque(f32),
+	AngularImpulse(f32),
+	Force(Position, Position),
+	LinearImpulse(Position, Position),
+}
+
+struct JointRef<'a, H> {
+	refs: agent::Key,
+	handle: H,
+	growing_radius: f32,
+	rest_angle: f32,
+	strength: f32,
+	mesh: &'a obj::Mesh,
+	flags: segment::Flags,
+	attachment: Option<segment::Attachment>,
+}
+
+impl<B: PhysicsBackend> System for PhysicsSystem<B> {
+	fn attach(&mut self, bus: &mut PubSub) {
+		self.inbox = Some(bus.subscribe(Box::new(|m| match *m {
+			Message::Event(Event::PickMinion(_)) => true,
+			_ => false,
+		})));
+	}
+
+	fn init(&mut self, world: &world::World) {
+		self.topology = world.topology().clone();
+		self.settings = world.scenario().physics.clone();
+		self.init_extent();
+	}
+
+	fn clear(&mut self) {
+		for i in &self.inbox {
+			i.drain();
+		}
+		self.handles.clear();
+		self.panels.clear();
+		self.severed.clear();
+		self.quarantined.clear();
+		self.picked.clear();
+		self.accumulator = 0.;
+		self.backend.clear();
+		self.contacts.clear();
+		self.extent_handle = None;
+		self.init_extent();
+	}
+
+	fn register(&mut self, agent: &world::agent::Agent) {
+		// build fixtures
+		let joint_refs = Self::build_fixtures(&mut self.backend, &agent);
+		// and then assemble them with joints
+		Self::build_joints(&mut self.backend, &joint_refs);
+		// record them
+		for JointRef { refs, handle, flags, .. } in joint_refs {
+			if !flags.intersects(segment::Flags::STATIC | segment::Flags::KINEMATIC) {
+				// bodies without panels are not subject to fluid drag
+				if let Some(segment) = agent.segment(refs.segment_index).filter(|s| s.material().fluid_drag > 0.) {
+					self.panels.insert(handle, Self::build_panels(segment));
+				}
+			}
+			self.handles.insert(refs, handle);
+		}
+	}
+
+	fn unregister(&mut self, agent: &world::agent::Agent) {
+		let object_id = agent.id();
+		let segments = agent.segments();
+		for segment in segments {
+			let refs = agent::Key::with_segment(object_id, segment.index);
+			if let Some(handle) = self.handles.remove(&refs) {
+				self.panels.remove(&handle);
+				self.backend.destroy_body(handle);
+			}
+		}
+	}
+
+	fn import(&mut self, world: &world::World) {
+		let messages = match self.inbox {
+			Some(ref drain) => drain.drain(),
+			None => Vec::new(),
+		};
+		if world.topology() != &self.topology {
+			self.topology = world.topology().clone();
+			self.init_extent();
+		}
+		self.picked.clear();
+		for message in messages {
+			if let Message::Event(Event::PickMinion(position)) = message {
+				let picked = self.pick(position);
+				if let Some(picked_id) = picked {
+					self.picked.insert(picked_id);
+				}
+			}
+		}
+		self.rebuild_severed(world);
+		for agent in world.agents(agent::AgentType::Minion).values() {
+			if agent.state.growth() > 0. {
+				self.rescale(agent)
+			}
+		}
+	}
+
+	fn update(&mut self, state: &world::AgentState, dt_sec: Seconds) {
+		use self::BodyUpdate::*;
+		let mut dynamic_updates = Vec::new();
+		let dt: f32 = dt_sec.into();
+		self.quarantined.clear();
+		for (h, body) in self.backend.bodies() {
+			let center = body.center;
+			let key = body.key;
+			if let Some(segment) = state.agent(key.agent_id).and_then(|c| c.segment(key.segment_index)) {
+				match segment.state.intent {
+					Intent::Move(force) => {
+						// in the fluid, most of the thrust has to come from the drag on flapping panels
+						let force = if self.panels.contains_key(&h) {
+							force * FLUID_MOVE_FORCE_RATIO
+						} else {
+							force
+						};
+						dynamic_updates.push((h, Force(center, force)))
+					}
+					Intent::Brake(force) => {
+						let linear_velocity = body.motion.velocity;
+						let comp = force.dot(linear_velocity);
+						if comp < 0. {
+							dynamic_updates.push((h, Force(center, force)));
+						}
+					}
+					Intent::PilotTo(force, ref target_angle) => {
+						if let Some(force) = force {
+							let linear_velocity = body.motion.velocity;
+							let speed2 = linear_velocity.magnitude2();
+							let drag_factor = (1. - speed2 * DRAG_COEFFICIENT).min(1.).max(0.);
+							dynamic_updates.push((h, Force(center, force * drag_factor)));
+						}
+						match *target_angle {
+							PilotRotation::LookAt(target) => {
+								let look_at_vector = target - center;
+								let target_angle = f32::atan2(-look_at_vector.x, look_at_vector.y);
+								dynamic_updates.push((h, Transform(body.transform.position, target_angle)));
+							}
+							PilotRotation::Orientation(direction) => {
+								let target_angle = f32::atan2(-direction.x, direction.y);
+								dynamic_updates.push((h, Transform(body.transform.position, target_angle)));
+							}
+							PilotRotation::Turn(angle) => {
+								dynamic_updates.push((h, Torque(angle)));
+							}
+							PilotRotation::FromVelocity => {
+								let linear_velocity = body.motion.velocity;
+								let target_angle = f32::atan2(-linear_velocity.x, linear_velocity.y);
+								dynamic_updates.push((h, Transform(body.transform.position, target_angle)));
+							}
+							PilotRotation::None => {}
+							//TODO: try physics!
+							//let angle = (*body).angle();
+							//let norm_diff = math::normalize_rad(target_angle - angle);
+							//body_updates.push((h, Torque(norm_diff * COMPASS_SPRING_POWER)))
+							//torques.push((h, norm_diff * COMPASS_SPRING_POWER));
+						}
+					}
+					Intent::RunAway(impulse) => dynamic_updates.push((h, LinearImpulse(center, impulse * dt))),
+					_ => {}
+				}
+			}
+		}
+
+		// impulses and transforms are applied once, forces are cleared by the solver after every step
+		let mut forces = Vec::new();
+		for (h, update) in dynamic_updates {
+			match update {
+				BodyUpdate::AngularImpulse(impulse) => self.backend.apply_angular_impulse(h, impulse),
+				BodyUpdate::LinearImpulse(application_point, impulse) => {
+					self.backend.apply_linear_impulse(h, impulse, application_point)
+				}
+				BodyUpdate::Transform(translation, rotation) => self.backend.set_transform(h, translation, rotation),
+				force => forces.push((h, force)),
+			}
+		}
+		let steps = self.advance(dt);
+		let substeps = self.settings.substeps.max(1);
+		let step_dt = self.settings.timestep / substeps as f32;
+		for _ in 0..steps * substeps {
+			for &(h, ref update) in &forces {
+				match *update {
+					BodyUpdate::Torque(torque) => self.backend.apply_torque(h, torque),
+					// bodies move between steps, forces keep pushing through the center of mass
+					BodyUpdate::Force(_, force) => {
+						let center = self.backend.body(h).center;
+						self.backend.apply_force(h, force, center)
+					}
+					_ => {}
+				}
+			}
+			self.apply_fluid_drag();
+			self.backend
+				.step(step_dt, self.settings.velocity_iterations, self.settings.position_iterations);
+			self.break_joints(1. / step_dt);
+		}
+		self.wrap_bodies();
+		if self.settings.diagnostics != DiagnosticsMode::Off {
+			self.diagnose();
+		}
+		self.contacts = self.backend.drain_contacts();
+	}
+
+	fn export(&self, world: &mut world::World, outbox: &Outbox) {
+		let strongest = self.contacts.strongest();
+		for (_, body) in self.backend.bodies() {
+			let key = body.key;
+			if let Some(agent) = world.agent_mut(key.agent_id) {
+				if let Some(segment) = agent.segment_mut(key.segment_index) {
+					segment.transform_to(body.transform);
+					segment.motion_to(body.motion);
+					segment.state.last_touched = strongest.get(&key).map(|&(partner, _)| partner);
+				}
+			}
+		}
+		for (_, agent) in world.agents_mut(agent::AgentType::Minion).iter_mut() {
+			agent.state.reset_growth()
+		}
+		for id in &self.quarantined {
+			if let Some(agent) = world.agent_mut(*id) {
+				agent.state.die();
+			}
+		}
+		for id in &self.picked {
+			outbox.post(Event::SelectMinion(*id).into());
+		}
+		// limbs are lost after all the segments have been updated, as indices change
+		let mut severed = self.severed.clone();
+		severed.sort_by_key(|key| ::std::cmp::Reverse(key.segment_index));
+		for key in severed {
+			world.sever_limb(outbox, key);
+		}
+		for event in &self.contacts.events {
+			outbox.post(event.clone().into());
+		}
+		for contact in self.contacts.impulses.values() {
+			outbox.post(ContactEvent::Impulse(contact.clone()).into());
+		}
+	}
+}
+
+impl<B: PhysicsBackend> Default for PhysicsSystem<B> {
+	fn default() -> Self {
+		PhysicsSystem {
+			inbox: None,
+			topology: Topology::default(),
+			extent_handle: None,
+			settings: PhysicsSettings::default(),
+			accumulator: 0.,
+			backend: B::default(),
+			handles: HashMap::with_capacity(5000),
+			panels: HashMap::with_capacity(5000),
+			picked: HashSet::with_capacity(100),
+			contacts: ContactLog::default(),
+			severed: Vec::new(),
+			quarantined: Vec::new(),
+		}
+	}
+}
+
+impl<B: PhysicsBackend> PhysicsSystem<B> {
+	/// Accumulates the elapsed time and returns how many fixed steps are due.
+	/// Time beyond `max_steps` is dropped, to avoid falling further and further behind
+	fn advance(&mut self, dt: f32) -> usize {
+		let timestep = self.settings.timestep;
+		self.accumulator += dt;
+		let due = (self.accumulator / timestep).floor() as usize;
+		let steps = due.min(self.settings.max_steps);
+		self.accumulator = if due > steps { 0. } else { self.accumulator - steps as f32 * timestep };
+		steps
+	}
+
+	fn init_extent(&mut self) {
+		if let Some(handle) = self.extent_handle.take() {
+			self.backend.destroy_body(handle);
+		}
+		// wrapping worlds have no walls
+		if let Some(boundary) = self.topology.boundary() {
+			let refs = agent::Key::with_id(EXTENT_ID);
+			let handle = self.backend.create_body(&BodyDef {
+				key: refs,
+				body_type: BodyType::Static,
+				transform: Transform::default(),
+				motion: Motion::default(),
+				linear_damping: 0.,
+				angular_damping: 0.,
+			});
+			let f_def = FixtureDef {
+				key: refs,
+				density: 0.,
+				restitution: 0.,
+				friction: 0.2,
+			};
+			self.backend.add_fixture(handle, &ShapeDef::Loop { vertices: boundary }, &f_def);
+			self.extent_handle = Some(handle);
+		}
+	}
+
+	/// Teleports agents whose core has left a wrapping world to the opposite edge.
+	/// All the segments of an agent are moved by the same offset, to keep joints intact
+	fn wrap_bodies(&mut self) {
+		if !self.topology.is_wrapping() {
+			return;
+		}
+		let bodies = self.backend.bodies();
+		let mut offsets = HashMap::new();
+		for &(_, ref body) in &bodies {
+			let key = body.key;
+			if key.segment_index == 0 && key.agent_id != EXTENT_ID {
+				let p = body.transform.position;
+				let wrapped = self.topology.wrap(p);
+				if wrapped != p {
+					offsets.insert(key.agent_id, wrapped - p);
+				}
+			}
+		}
+		if offsets.is_empty() {
+			return;
+		}
+		for (h, body) in bodies {
+			let agent_id = body.key.agent_id;
+			if let Some(offset) = offsets.get(&agent_id) {
+				self.backend
+					.set_transform(h, body.transform.position + *offset, body.transform.angle);
+			}
+		}
+	}
+
+	/// Checks energy, momentum and stability after an update.
+	/// Agents which have blown up are reported, and taken out of the simulation in quarantine mode
+	fn diagnose(&mut self) {
+		let bodies = self.backend.bodies();
+		let diagnostics = Diagnostics::measure(
+			bodies.iter().map(|&(_, ref body)| body),
+			self.settings.max_speed,
+			self.settings.max_spin,
+		);
+		info!(
+			"physics: {} bodies, energy {:.3}, momentum ({:.3}, {:.3}), angular momentum {:.3}",
+			diagnostics.bodies,
+			diagnostics.kinetic_energy,
+			diagnostics.momentum.x,
+			diagnostics.momentum.y,
+			diagnostics.angular_momentum
+		);
+		if diagnostics.unstable.is_empty() {
+			return;
+		}
+		warn!("physics: unstable agents {:?}", diagnostics.unstable);
+		if self.settings.diagnostics == DiagnosticsMode::Quarantine {
+			for (h, body) in bodies {
+				let agent_id = body.key.agent_id;
+				if diagnostics.unstable.contains(&agent_id) {
+					self.handles.remove(&body.key);
+					self.panels.remove(&h);
+					self.backend.destroy_body(h);
+				}
+			}
+			self.quarantined.extend(diagnostics.unstable);
+		}
+	}
+
+	/// Splits the outline of a segment into panels, normals pointing outwards
+	fn build_panels(segment: &segment::Segment) -> Box<[Panel]> {
+		let fluid_drag = segment.material().fluid_drag;
+		let n = segment.mesh().vertices.len();
+		(0..n)
+			.filter_map(|i| {
+				let a = segment.growing_scaled_vertex(i);
+				let b = segment.growing_scaled_vertex((i + 1) % n);
+				let edge = b - a;
+				let length = edge.magnitude();
+				if length <= B2_LINEAR_SLOP {
+					return None;
+				}
+				let midpoint = (a + b) * 0.5;
+				let normal = Position::new(edge.y, -edge.x) / length;
+				let normal = if normal.dot(midpoint) < 0. { -normal } else { normal };
+				Some(Panel {
+					midpoint,
+					normal,
+					area: length * fluid_drag,
+				})
+			}).collect::<Vec<_>>()
+			.into_boxed_slice()
+	}
+
+	/// Quadratic drag on each panel, from the velocity of the fluid relative to its midpoint.
+	/// Panels resist much more across than along their surface, so flapping limbs produce thrust
+	fn apply_fluid_drag(&mut self) {
+		for (h, panels) in &self.panels {
+			let body = self.backend.body(*h);
+			for panel in panels.iter() {
+				let point = body.world_point(panel.midpoint);
+				let velocity = body.velocity_at(point);
+				let normal = body.world_vector(panel.normal);
+				let normal_speed = velocity.dot(normal);
+				let tangential = velocity - normal * normal_speed;
+				// only panels advancing into the fluid are pushed back across
+				let normal_drag = if normal_speed > 0. {
+					normal * -(normal_speed * normal_speed)
+				} else {
+					Position::new(0., 0.)
+				};
+				let tangential_drag = tangential * -(tangential.magnitude() * FLUID_DRAG_TANGENTIAL_RATIO);
+				let force = (normal_drag + tangential_drag) * (0.5 * FLUID_DENSITY * panel.area);
+				self.backend.apply_force(*h, force, point);
+			}
+		}
+	}
+
+	/// Destroys the joints pulled beyond their strength, their distal segments are going to be lost
+	fn break_joints(&mut self, inv_dt: f32) {
+		let broken = self
+			.backend
+			.joint_loads(inv_dt)
+			.into_iter()
+			.filter(|&(_, limit, force)| force > limit.strength)
+			.map(|(h, limit, _)| (h, limit.distal))
+			.collect::<Vec<_>>();
+		for (h, distal) in broken {
+			self.backend.destroy_joint(h);
+			self.severed.push(distal);
+		}
+	}
+
+	/// Agents which have lost limbs are rebuilt from scratch, as their segments have been renumbered
+	fn rebuild_severed(&mut self, world: &world::World) {
+		if self.severed.is_empty() {
+			return;
+		}
+		let ids = self.severed.drain(..).map(|key| key.agent_id).collect::<HashSet<_>>();
+		let stale = self
+			.handles
+			.keys()
+			.filter(|key| {
+				let agent_id = key.agent_id;
+				ids.contains(&agent_id)
+			}).cloned()
+			.collect::<Vec<_>>();
+		for key in stale {
+			if let Some(handle) = self.handles.remove(&key) {
+				self.panels.remove(&handle);
+				self.backend.destroy_body(handle);
+			}
+		}
+		for id in ids {
+			if let Some(agent) = world.agent(id) {
+				self.register(agent);
+			}
+		}
+	}
+
+	fn refresh_registration(&mut self, agent: &world::agent::Agent) {
+		self.unregister(agent);
+		self.register(agent);
+	}
+
+	/// Resizes the fixtures of a grown agent in place, so that bodies keep their position and velocity.
+	/// Joint anchors can't be moved, the box2d bindings have no setter for them: the joints are rebuilt
+	/// between the same bodies instead, which resets their accumulated impulses
+	fn rescale(&mut self, agent: &world::agent::Agent) {
+		let object_id = agent.id();
+		let mut handles = Vec::new();
+		for segment in agent.segments() {
+			let handle = self.handles.get(&agent::Key::with_segment(object_id, segment.index)).cloned();
+			match handle {
+				Some(handle) => handles.push(handle),
+				None => return self.refresh_registration(agent),
+			}
+		}
+		let mut joints = HashSet::new();
+		for &handle in &handles {
+			joints.extend(self.backend.body_joints(handle));
+		}
+		for joint in joints {
+			self.backend.destroy_joint(joint);
+		}
+		let mut joint_refs = Vec::new();
+		for (segment, handle) in agent.segments().iter().zip(handles) {
+			let refs = agent::Key::with_segment(object_id, segment.index);
+			self.backend.clear_fixtures(handle);
+			Self::build_fixture_for_segment(
+				&mut self.backend,
+				handle,
+				segment.state.maturity(),
+				object_id,
+				segment.index as usize,
+				refs,
+				segment.material(),
+				segment.mesh(),
+			);
+			if self.panels.contains_key(&handle) {
+				self.panels.insert(handle, Self::build_panels(segment));
+			}
+			joint_refs.push(Self::joint_ref(refs, handle, segment));
+		}
+		Self::build_joints(&mut self.backend, &joint_refs);
+	}
+
+	#[allow(too_many_arguments)]
+	fn build_fixture_for_segment(
+		backend: &mut B,
+		handle: B::Body,
+		maturity: f32,
+		object_id: obj::Id,
+		segment_index: usize,
+		refs: agent::Key,
+		material: &Material,
+		mesh: &Mesh,
+	)
+	{
+		fn make_circle_shape(grown_radius: f32) -> ShapeDef { ShapeDef::Circle { radius: grown_radius } }
+
+		fn make_safe_poly_shape(grown_radius: f32, vertices: &[Position]) -> ShapeDef {
+			// from box2d code, checks unique vertices
+			let mut dupes = 0;
+			for (i, v1) in vertices.iter().enumerate() {
+				for v2 in &vertices[(i + 1)..] {
+					let d2 = (v2 - v1).magnitude2();
+					if d2 < 0.5f32 * B2_LINEAR_SLOP {
+						dupes += 1;
+					}
+				}
+			}
+			// if we have enough non-degenerate vertices, we hand them over to the backend
+			// TOOD: optimize (don't send dupes)
+			if vertices.len() - dupes > 2 {
+				ShapeDef::Polygon {
+					vertices: vertices.to_vec().into_boxed_slice(),
+				}
+			} else {
+				// tiny circle, failover
+				make_circle_shape(grown_radius)
+			}
+		}
+
+		let mut shapes = Vec::new();
+		match mesh.shape {
+			obj::Shape::Ball { radius } => {
+				shapes.push((refs, make_circle_shape(radius * maturity)));
+			}
+			obj::Shape::Box { radius, ratio } => {
+				let (hx, hy) = (radius * ratio, radius * maturity);
+				let rect_vertices = vec![
+					Position::new(-hx, -hy),
+					Position::new(hx, -hy),
+					Position::new(hx, hy),
+					Position::new(-hx, hy),
+				];
+				shapes.push((
+					refs,
+					ShapeDef::Polygon {
+						vertices: rect_vertices.into_boxed_slice(),
+					},
+				));
+			}
+			obj::Shape::Poly { radius, n, .. } => {
+				let grown_radius = radius * maturity;
+				let p = &mesh.vertices;
+				let offset = if n < 0 { 1 } else { 0 };
+				let mut vertices = Vec::new();
+				for i in 0..n.abs() {
+					vertices.push(p[2 * i as usize + offset] * grown_radius);
+				}
+				shapes.push((refs, make_safe_poly_shape(grown_radius, vertices.as_slice())));
+			}
+			obj::Shape::Star { radius, n, .. } => {
+				let grown_radius = radius * maturity;
+				if grown_radius < 0.05 * f32::from(n) {
+					shapes.push((refs, make_circle_shape(grown_radius)));
+				} else {
+					let p = &mesh.vertices;
+					for i in 0..n {
+						let i1 = (i * 2 + 1) as usize;
+						let i2 = (i * 2) as usize;
+						let i3 = ((i * 2 + (n * 2) - 1) % (n * 2)) as usize;
+						let (p1, p2, p3) = match mesh.winding() {
+							obj::Winding::CW => (p[i1], p[i2], p[i3]),
+							obj::Winding::CCW => (p[i1], p[i3], p[i2]),
+						};
+						let quad_vertices = &[
+							Position::new(0., 0.),
+							p1 * grown_radius,
+							p2 * grown_radius,
+							p3 * grown_radius,
+						];
+						let refs = agent::Key::with_bone(object_id, segment_index as u8, i as u8);
+						shapes.push((refs, make_safe_poly_shape(grown_radius, quad_vertices)));
+					}
+				}
+			}
+			obj::Shape::Triangle { radius, .. } => {
+				let grown_radius = radius * maturity;
+				let p = &mesh.vertices;
+				let (p1, p2, p3) = match mesh.winding() {
+					obj::Winding::CW => (p[0], p[2], p[1]),
+					obj::Winding::CCW => (p[0], p[1], p[2]),
+				};
+				let tri_vertices = &[p1 * grown_radius, p2 * grown_radius, p3 * grown_radius];
+				shapes.push((refs, make_safe_poly_shape(grown_radius, tri_vertices)));
+			}
+		};
+		for (key, shape) in shapes {
+			backend.add_fixture(handle, &shape, &Self::fixture_def(key, material));
+		}
+	}
+
+	fn fixture_def(key: agent::Key, material: &Material) -> FixtureDef {
+		FixtureDef {
+			key,
+			density: material.density,
+			restitution: material.restitution,
+			friction: material.friction,
+		}
+	}
+
+	fn joint_ref(refs: agent::Key, handle: B::Body, segment: &segment::Segment) -> JointRef<B::Body> {
+		let growing_radius = segment.growing_radius();
+		JointRef {
+			refs,
+			growing_radius,
+			rest_angle: segment.rest_angle,
+			strength: segment.material().joint_strength * growing_radius.powi(2),
+			handle,
+			mesh: segment.mesh(),
+			flags: segment.flags,
+			attachment: segment.attached_to,
+		}
+	}
+
+	fn build_fixtures<'a>(backend: &mut B, agent: &'a world::agent::Agent) -> Vec<JointRef<'a, B::Body>> {
+		let object_id = agent.id();
+		let segments = agent.segments();
+		segments
+			.into_iter()
+			.enumerate()
+			.map(|(segment_index, segment)| {
+				let material = segment.material();
+				let refs = agent::Key::with_segment(object_id, segment_index as u8);
+				let handle = backend.create_body(&BodyDef {
+					key: refs,
+					body_type: if segment.flags.contains(segment::Flags::STATIC) {
+						BodyType::Static
+					} else if segment.flags.contains(segment::Flags::KINEMATIC) {
+						BodyType::Kinematic
+					} else {
+						BodyType::Dynamic
+					},
+					transform: segment.transform().clone(),
+					motion: segment.motion.clone(),
+					linear_damping: material.linear_damping,
+					angular_damping: material.angular_damping,
+				});
+				Self::build_fixture_for_segment(
+					backend,
+					handle,
+					segment.state.maturity(),
+					object_id,
+					segment_index,
+					refs,
+					material,
+					segment.mesh(),
+				);
+				Self::joint_ref(refs, handle, segment)
+			}).collect::<Vec<_>>()
+	}
+
+	fn build_joints(backend: &mut B, joint_refs: &[JointRef<B::Body>]) {
+		for &JointRef {
+			refs,
+			handle: distal,
+			mesh,
+			growing_radius,
+			rest_angle,
+			attachment,
+			flags,
+			strength,
+			..
+		} in joint_refs
+		{
+			if let Some(attachment) = attachment {
+				let upstream = &joint_refs[attachment.index as usize];
+				let medial = upstream.handle;
+				let angle_delta = rest_angle - upstream.rest_angle; //world.body(distal).angle() - world.body(medial).angle();
+				let v0 = upstream.mesh.vertices[attachment.attachment_point as usize] * upstream.growing_radius;
+				//let angle_delta = v0.x.atan2(-v0.y) as f32;//consts::PI;
+				let v1 = mesh.vertices[0] * growing_radius;
+				let kind = if flags.contains(world::segment::Flags::JOINT) {
+					JointKind::Revolute {
+						lower_angle: JOINT_LOWER_ANGLE,
+						upper_angle: JOINT_UPPER_ANGLE,
+					}
+				} else {
+					JointKind::Weld {
+						frequency: JOINT_FREQUENCY,
+						damping_ratio: JOINT_DAMPING_RATIO,
+					}
+				};
+				backend.create_joint(&JointDef {
+					kind,
+					body_a: medial,
+					body_b: distal,
+					anchor_a: v0,
+					anchor_b: v1,
+					reference_angle: angle_delta,
+					limit: JointLimit { distal: refs, strength },
+				});
+			}
+		}
+	}
+
+	/// Minions with any fixture overlapping an area
+	pub fn pick_area(&self, area: Rect) -> Vec<Id> {
+		self.backend
+			.query_area(area)
+			.into_iter()
+			.map(|key| key.id())
+			.filter(|id| id.type_of() == agent::AgentType::Minion)
+			.collect::<HashSet<_>>()
+			.into_iter()
+			.collect()
+	}
+
+	pub fn pick(&self, pos: Position) -> Option<Id> { self.backend.query_point(self.topology.wrap(pos)).map(|key| key.id()) }
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use backend::testing;
+	use test::Bencher;
+
+	/// A world holding a single minion made of several segments, not fully grown yet
+	fn world_with_minion() -> (world::World, Id) {
+		let mut world = testing::new_world();
+		loop {
+			let id = world.new_minion(Position::new(0., 0.), Motion::default());
+			if world.agent(id).map(|agent| agent.segments().len() > 1).unwrap_or(false) {
+				for segment in world.agent_mut(id).unwrap().segments_mut() {
+					segment.state.set_maturity(0.5);
+				}
+				return (world, id);
+			}
+		}
+	}
+
+	fn grow(world: &mut world::World, id: Id) {
+		for segment in world.agent_mut(id).unwrap().segments_mut() {
+			segment.state.set_maturity(1.);
+		}
+	}
+
+	fn joint_limits<B: PhysicsBackend>(physics: &PhysicsSystem<B>) -> Vec<(obj::SegmentIndex, f32)> {
+		let mut limits: Vec<_> = physics
+			.backend
+			.joint_loads(1.)
+			.into_iter()
+			.map(|(_, limit, _)| (limit.distal.segment_index, limit.strength))
+			.collect();
+		limits.sort_by_key(|&(segment_index, _)| segment_index);
+		limits
+	}
+
+	fn check_rescaled_bodies<B: PhysicsBackend>() {
+		let (mut world, id) = world_with_minion();
+		let mut physics = PhysicsSystem::<B>::default();
+		physics.register(world.agent(id).unwrap());
+		let handles = physics.handles.clone();
+		let joint_count = physics.backend.joint_loads(1.).len();
+		let masses: HashMap<_, _> = handles.iter().map(|(&key, &handle)| (key, physics.backend.body(handle).mass)).collect();
+		assert!(joint_count > 0);
+
+		grow(&mut world, id);
+		physics.rescale(world.agent(id).unwrap());
+
+		assert!(handles == physics.handles);
+		for (&key, &handle) in &handles {
+			let body = physics.backend.body(handle);
+			assert_eq!(key, body.key);
+			assert!(body.mass > masses[&key]);
+		}
+		assert_eq!(joint_count, physics.backend.joint_loads(1.).len());
+
+		// joints and limits are those a newly registered grown agent gets
+		let mut registered = PhysicsSystem::<B>::default();
+		registered.register(world.agent(id).unwrap());
+		assert_eq!(joint_limits(&registered), joint_limits(&physics));
+	}
+
+	fn bench_rescale_on<B: PhysicsBackend>(b: &mut Bencher) {
+		let (world, id) = world_with_minion();
+		let mut physics = PhysicsSystem::<B>::default();
+		let agent = world.agent(id).unwrap();
+		physics.register(agent);
+		b.iter(|| physics.rescale(agent));
+	}
+
+	fn bench_refresh_registration_on<B: PhysicsBackend>(b: &mut Bencher) {
+		let (world, id) = world_with_minion();
+		let mut physics = PhysicsSystem::<B>::default();
+		let agent = world.agent(id).unwrap();
+		physics.register(agent);
+		b.iter(|| physics.refresh_registration(agent));
+	}
+
+	#[test]
+	fn rescaled_bodies_keep_their_handles_keys_and_joints() { check_rescaled_bodies::<CircleBackend>(); }
+
+	#[test]
+	#[cfg(feature = "box2d")]
+	fn rescaled_box2d_bodies_keep_their_handles_keys_and_joints() { check_rescaled_bodies::<Box2dBackend>(); }
+
+	#[bench]
+	fn bench_rescale(b: &mut Bencher) { bench_rescale_on::<CircleBackend>(b); }
+
+	#[bench]
+	fn bench_refresh_registration(b: &mut Bencher) { bench_refresh_registration_on::<CircleBackend>(b); }
+
+	#[bench]
+	#[cfg(feature = "box2d")]
+	fn bench_rescale_box2d(b: &mut Bencher) { bench_rescale_on::<Box2dBackend>(b); }
+
+	#[bench]
+	#[cfg(feature = "box2d")]
+	fn bench_refresh_registration_box2d(b: &mut Bencher) { bench_refresh_registration_on::<Box2dBackend>(b); }
+}
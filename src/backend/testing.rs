@@ -0,0 +1,32 @@
+//! Fixtures shared by the unit tests
+use app::constants::*;
+use backend::world;
+use core::resource::filesystem::ResourceLoaderBuilder;
+use std::env;
+use std::fs;
+use std::path;
+use std::process;
+
+/// An empty world, with the gene pool from the resources folder
+pub fn new_world() -> world::World {
+	let res = ResourceLoaderBuilder::new().add(path::Path::new(CONFIG_DIR_RESOURCES)).build();
+	world::World::new(&res, DEFAULT_MINION_GENE_POOL_FILE)
+}
+
+/// A world seeded with the minions of its gene pool
+pub fn populated_world() -> world::World {
+	let mut world = new_world();
+	world.init_minions();
+	world
+}
+
+/// A path in the temporary folder, unique to the test run
+pub fn temp_path(name: &str) -> path::PathBuf { env::temp_dir().join(format!("rust-oids-{}-{}", process::id(), name)) }
+
+/// An empty folder in the temporary folder, unique to the test run
+pub fn temp_dir(name: &str) -> path::PathBuf {
+	let dir = temp_path(name);
+	fs::remove_dir_all(&dir).is_ok();
+	fs::create_dir_all(&dir).unwrap();
+	dir
+}
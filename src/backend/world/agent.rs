@@ -2,6 +2,8 @@ use app::constants::*;
 use backend::obj;
 use backend::obj::*;
 use backend::world::gen::Dna;
+use backend::world::infection::Infection;
+use backend::world::infection::Pathogen;
 use backend::world::segment;
 use backend::world::segment::Segment;
 use core::clock::*;
@@ -127,6 +129,22 @@ pub type OutputVector<S> = [S; N_WEIGHTS];
 pub type WeightVector<T> = [T; N_WEIGHTS];
 pub type WeightMatrix<T> = [WeightVector<T>; N_WEIGHTS];
 
+/// What a minion does when something other than food touches it
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TouchResponse {
+	Flee,
+	Ignore,
+	Follow,
+	Push,
+}
+
+const TOUCH_RESPONSES: &[TouchResponse] = &[
+	TouchResponse::Flee,
+	TouchResponse::Ignore,
+	TouchResponse::Follow,
+	TouchResponse::Push,
+];
+
 #[derive(Clone, Default, Debug)]
 pub struct GBrain<T: Copy + Default> {
 	pub hunger: T,
@@ -138,6 +156,7 @@ pub struct GBrain<T: Copy + Default> {
 	pub weights_in: WeightMatrix<T>,
 	pub weights_hidden: WeightMatrix<T>,
 	pub weights_out: WeightMatrix<T>,
+	pub weights_touch: WeightMatrix<T>,
 }
 
 pub trait TypedBrain {
@@ -155,6 +174,7 @@ where S: Copy + Float {
 	fn rest(&self) -> S;
 	fn thrust(&self) -> S;
 	fn response(&self, input: &InputVector<S>) -> OutputVector<S>;
+	fn touch_response(&self, input: &InputVector<S>) -> TouchResponse;
 }
 
 pub trait Layer<S, T>
@@ -201,6 +221,18 @@ where
 		let output_out = Self::layer(&output_hidden, &self.weights_out);
 		output_out
 	}
+
+	fn touch_response(&self, input: &InputVector<S>) -> TouchResponse {
+		// single layer, the strongest output wins
+		let output = Self::layer(input, &self.weights_touch);
+		let mut best = 0;
+		for (i, value) in output.iter().enumerate() {
+			if *value > output[best] {
+				best = i;
+			}
+		}
+		TOUCH_RESPONSES[best]
+	}
 }
 
 impl<T> TypedBrain for GBrain<T>
@@ -221,6 +253,23 @@ bitflags! {
 	}
 }
 
+/// How long a spore stays dormant and what wakes it up
+#[derive(Clone, Debug)]
+pub struct Dormancy {
+	/// minimum time before the spore can hatch
+	pub duration: Seconds,
+	/// time after which an unhatched spore withers away
+	pub window: Seconds,
+	/// strength of the random walk while dormant
+	pub drift: f32,
+	/// how many resources nearby are needed to trigger hatching
+	pub food_trigger: f32,
+}
+
+impl Dormancy {
+	pub fn lifespan(&self) -> Seconds { self.duration + self.window }
+}
+
 #[derive(Clone, Debug)]
 pub struct Limits {
 	max_energy: f32,
@@ -259,6 +308,9 @@ pub struct State {
 	target_position: Position,
 	limits: Limits,
 	foreign_dna: Option<Dna>,
+	infection: Option<Infection>,
+	/// the founder this agent descends from, none for founders themselves
+	lineage: Option<Id>,
 	trajectory: util::History<Position>,
 }
 
@@ -266,6 +318,8 @@ impl State {
 	#[inline]
 	pub fn lifecycle(&self) -> &Hourglass { &self.lifecycle }
 
+	pub fn restore_lifecycle(&mut self, left: Seconds, timer: &Timer) { self.lifecycle = Hourglass::new(left, timer); }
+
 	pub fn energy(&self) -> f32 { self.energy }
 
 	pub fn energy_ratio(&self) -> f32 { self.energy / self.limits.max_energy }
@@ -314,6 +368,22 @@ impl State {
 
 	pub fn foreign_dna(&self) -> &Option<Dna> { &self.foreign_dna }
 
+	pub fn infection(&self) -> Option<&Infection> { self.infection.as_ref() }
+
+	pub fn infection_mut(&mut self) -> Option<&mut Infection> { self.infection.as_mut() }
+
+	pub fn is_infected(&self) -> bool { self.infection.is_some() }
+
+	pub fn infect(&mut self, pathogen: Pathogen) { self.infection = Some(Infection::new(pathogen)); }
+
+	pub fn restore_infection(&mut self, infection: Option<Infection>) { self.infection = infection; }
+
+	pub fn recover(&mut self) { self.infection = None; }
+
+	pub fn lineage(&self) -> Option<Id> { self.lineage }
+
+	pub fn descend_from(&mut self, founder: Id) { self.lineage = Some(founder); }
+
 	pub fn toggle_selection(&mut self) { self.flags ^= Flags::SELECTED; }
 	#[allow(unused)]
 	pub fn select(&mut self) { self.flags |= Flags::SELECTED; }
@@ -357,6 +427,10 @@ pub struct Agent {
 	brain: Brain,
 	dna: Dna,
 	gender: u8,
+	immunity: f32,
+	dormancy: Option<Dormancy>,
+	/// segments severed since the agent developed, in order, so that its body can be rebuilt
+	severed: Vec<SegmentIndex>,
 	pub state: State,
 	pub segments: Box<[Segment]>,
 }
@@ -381,6 +455,9 @@ impl Agent {
 
 	pub fn id(&self) -> Id { self.id }
 
+	/// The founder of the lineage, the agent itself if it was not born from another one
+	pub fn lineage(&self) -> Id { self.state.lineage().unwrap_or(self.id) }
+
 	#[inline]
 	pub fn gender(&self) -> u8 { self.gender }
 
@@ -398,6 +475,11 @@ impl Agent {
 
 	pub fn brain(&self) -> &Brain { &self.brain }
 
+	pub fn dormancy(&self) -> Option<&Dormancy> { self.dormancy.as_ref() }
+
+	#[inline]
+	pub fn immunity(&self) -> f32 { self.immunity }
+
 	pub fn first_segment(&self, flags: segment::Flags) -> Option<Segment> {
 		self.segments
 			.iter()
@@ -405,13 +487,61 @@ impl Agent {
 			.cloned()
 	}
 
+	/// Detaches a segment and everything attached downstream of it, returning the lost segments.
+	/// The remaining segments are renumbered, keeping their attachments consistent
+	pub fn sever(&mut self, index: SegmentIndex) -> Box<[Segment]> {
+		let n = self.segments.len();
+		if index == 0 || index as usize >= n {
+			return Box::new([]);
+		}
+		// parents always come before their children
+		let mut lost = vec![false; n];
+		for (i, segment) in self.segments.iter().enumerate() {
+			let is_lost = i == index as usize || segment.attached_to.map(|a| lost[a.index as usize]).unwrap_or(false);
+			lost[i] = is_lost;
+		}
+		let mut renumbered = vec![0 as SegmentIndex; n];
+		let mut kept = Vec::new();
+		let mut severed = Vec::new();
+		for (i, segment) in self.segments.iter().enumerate() {
+			if lost[i] {
+				severed.push(segment.clone());
+			} else {
+				renumbered[i] = kept.len() as SegmentIndex;
+				kept.push(segment.clone());
+			}
+		}
+		for segment in &mut kept {
+			segment.index = renumbered[segment.index as usize];
+			if let Some(ref mut attachment) = segment.attached_to {
+				attachment.index = renumbered[attachment.index as usize];
+			}
+		}
+		self.segments = kept.into_boxed_slice();
+		self.severed.push(index);
+		severed.into_boxed_slice()
+	}
+
+	/// Indices of the severed segments, as they were when each was severed
+	pub fn severed(&self) -> &[SegmentIndex] { &self.severed }
+
 	pub fn reset_body_charge(&mut self) {
 		self.segments[0]
 			.state
 			.reset_charge(PLAYER_CHARGE_INITIAL_VALUE, PLAYER_CHARGE_REST_VALUE)
 	}
 
-	pub fn new(id: Id, gender: u8, brain: &Brain, dna: &Dna, segments: Box<[Segment]>, timer: &Timer) -> Self {
+	pub fn new(
+		id: Id,
+		gender: u8,
+		immunity: f32,
+		brain: &Brain,
+		dormancy: &Option<Dormancy>,
+		dna: &Dna,
+		segments: Box<[Segment]>,
+		timer: &Timer,
+	) -> Self
+	{
 		const SCALE: f32 = 100.;
 		let max_energy = SCALE * segments
 			.iter()
@@ -421,7 +551,13 @@ impl Agent {
 			id,
 			state: State {
 				flags: Flags::ACTIVE,
-				lifecycle: Hourglass::new(Seconds::new(5.), timer),
+				lifecycle: Hourglass::new(
+					dormancy
+						.as_ref()
+						.map(|d| d.lifespan())
+						.unwrap_or_else(|| Seconds::new(DEFAULT_LIFESPAN)),
+					timer,
+				),
 				energy: max_energy * 0.5,
 				growth: 0.,
 				phase: 0.,
@@ -429,9 +565,14 @@ impl Agent {
 				target_position: segments[0].transform.position,
 				limits: Limits { max_energy },
 				foreign_dna: None,
+				infection: None,
+				lineage: None,
 				trajectory: util::History::new(600),
 			},
 			brain: brain.clone(),
+			dormancy: dormancy.clone(),
+			severed: Vec::new(),
+			immunity,
 			gender,
 			dna: dna.clone(),
 			segments,
@@ -0,0 +1,53 @@
+use backend::world::agent;
+use core::geometry::Position;
+
+/// A physical contact between a segment and one of its partners, as seen from the segment
+#[derive(Clone, Debug)]
+pub struct Contact {
+	pub segment: agent::Key,
+	pub partner: agent::Key,
+	/// where the two bodies touch, in world coordinates
+	pub point: Position,
+	/// direction from the segment towards its partner
+	pub normal: Position,
+	/// normal impulse accumulated over the last update, zero when the contact has just begun
+	pub impulse: f32,
+}
+
+impl Contact {
+	/// The same contact, as seen from the partner
+	pub fn mirror(&self) -> Self {
+		Contact {
+			segment: self.partner,
+			partner: self.segment,
+			point: self.point,
+			normal: -self.normal,
+			impulse: self.impulse,
+		}
+	}
+}
+
+/// Posted once for each side of a contact, so subscribers only need to look at their own segments
+#[derive(Clone, Debug)]
+pub enum ContactEvent {
+	Begin(Contact),
+	/// the contact persisted through an update, pushing with the given impulse
+	Impulse(Contact),
+	End(agent::Key, agent::Key),
+}
+
+impl ContactEvent {
+	pub fn segment(&self) -> agent::Key {
+		match *self {
+			ContactEvent::Begin(ref contact) | ContactEvent::Impulse(ref contact) => contact.segment,
+			ContactEvent::End(segment, _) => segment,
+		}
+	}
+
+	pub fn partner(&self) -> agent::Key {
+		match *self {
+			ContactEvent::Begin(ref contact) | ContactEvent::Impulse(ref contact) => contact.partner,
+			ContactEvent::End(_, partner) => partner,
+		}
+	}
+}
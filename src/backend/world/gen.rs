@@ -355,5 +355,16 @@ impl Generator for Genome {
 	}
 }
 
+/// Fraction of matching bits between two genomes, 1 for identical twins
+pub fn similarity(a: &[u8], b: &[u8]) -> f32 {
+	let len = cmp::min(a.len(), b.len());
+	if len == 0 {
+		0.
+	} else {
+		let matching: u32 = a.iter().zip(b.iter()).map(|(x, y)| (!(x ^ y)).count_ones()).sum();
+		matching as f32 / bit_count(len) as f32
+	}
+}
+
 #[cfg(test)]
 mod tests {}
@@ -0,0 +1,279 @@
+use app::constants::*;
+use backend::obj::*;
+use backend::world::agent::AgentMap;
+use backend::world::agent::AgentType;
+use backend::world::topology::Topology;
+use cgmath::InnerSpace;
+use core::geometry::*;
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+#[derive(Clone, Copy, Debug)]
+pub struct Entry {
+	pub id: Id,
+	pub position: Position,
+}
+
+type Cell = (i32, i32);
+
+/// Uniform grid of the active agents, rebuilt every tick, for proximity queries by agent type.
+/// Distances follow the world topology, so queries see across the edges of a wrapping world
+pub struct SpatialIndex {
+	topology: Topology,
+	cell_size: f32,
+	cols: i32,
+	rows: i32,
+	cells: HashMap<(AgentType, Cell), Vec<Entry>>,
+}
+
+impl Default for SpatialIndex {
+	fn default() -> Self { SpatialIndex::new(SPATIAL_INDEX_CELL_SIZE) }
+}
+
+fn modulo(x: i32, m: i32) -> i32 { ((x % m) + m) % m }
+
+impl SpatialIndex {
+	pub fn new(cell_size: f32) -> Self {
+		SpatialIndex {
+			topology: Topology::default(),
+			cell_size,
+			cols: 1,
+			rows: 1,
+			cells: HashMap::new(),
+		}
+	}
+
+	pub fn rebuild<'a, I>(&mut self, topology: &Topology, swarms: I)
+	where I: IntoIterator<Item = (AgentType, &'a AgentMap)> {
+		self.reset(topology);
+		for (agent_type, agents) in swarms {
+			for agent in agents.values().filter(|a| a.state.is_active()) {
+				self.insert(agent_type, agent.id(), agent.transform().position);
+			}
+		}
+	}
+
+	/// Empties the index and sizes its grid for a topology
+	pub fn reset(&mut self, topology: &Topology) {
+		let extent = topology.extent();
+		self.topology = topology.clone();
+		self.cols = (((extent.max.x - extent.min.x) / self.cell_size).ceil() as i32).max(1);
+		self.rows = (((extent.max.y - extent.min.y) / self.cell_size).ceil() as i32).max(1);
+		self.cells.clear();
+	}
+
+	pub fn insert(&mut self, agent_type: AgentType, id: Id, position: Position) {
+		let position = self.topology.wrap(position);
+		let cell = self.cell_of(position);
+		self.cells
+			.entry((agent_type, cell))
+			.or_insert_with(Vec::new)
+			.push(Entry { id, position });
+	}
+
+	fn coords_of(&self, p: Position) -> Cell {
+		let extent = self.topology.extent();
+		(
+			((p.x - extent.min.x) / self.cell_size).floor() as i32,
+			((p.y - extent.min.y) / self.cell_size).floor() as i32,
+		)
+	}
+
+	/// Agents outside the extent of a bounded world are kept in the cells along its border
+	fn cell_of(&self, p: Position) -> Cell {
+		let (x, y) = self.coords_of(p);
+		(x.max(0).min(self.cols - 1), y.max(0).min(self.rows - 1))
+	}
+
+	fn span(&self, from: i32, to: i32, size: i32) -> Vec<i32> {
+		if !self.topology.is_wrapping() {
+			(from.max(0).min(size - 1)..=to.max(0).min(size - 1)).collect()
+		} else if to - from + 1 >= size {
+			(0..size).collect()
+		} else {
+			(from..=to).map(|i| modulo(i, size)).collect()
+		}
+	}
+
+	/// All the agents of a type within `radius` of a position, in no particular order
+	pub fn within(&self, agent_type: AgentType, p: Position, radius: f32) -> Vec<Entry> {
+		let (x0, y0) = self.coords_of(p - Position::new(radius, radius));
+		let (x1, y1) = self.coords_of(p + Position::new(radius, radius));
+		let rows = self.span(y0, y1, self.rows);
+		let r2 = radius * radius;
+		let mut found = Vec::new();
+		for x in self.span(x0, x1, self.cols) {
+			for &y in &rows {
+				if let Some(entries) = self.cells.get(&(agent_type, (x, y))) {
+					found.extend(
+						entries
+							.iter()
+							.filter(|e| self.topology.delta(p, e.position).magnitude2() <= r2),
+					);
+				}
+			}
+		}
+		found
+	}
+
+	/// Up to `k` agents of a type within `max_radius` of a position, nearest first.
+	/// The search radius starts from a single cell and doubles until enough agents are found
+	pub fn nearest(&self, agent_type: AgentType, p: Position, k: usize, max_radius: f32) -> Vec<Entry> {
+		let extent = self.topology.extent();
+		let diagonal = (extent.max - extent.min).magnitude();
+		let mut radius = self.cell_size.min(max_radius);
+		loop {
+			let mut found = self.within(agent_type, p, radius);
+			if found.len() >= k || radius >= max_radius || radius >= diagonal {
+				let distance = |e: &Entry| self.topology.delta(p, e.position).magnitude2();
+				found.sort_by(|a, b| distance(a).partial_cmp(&distance(b)).unwrap_or(Ordering::Equal));
+				found.truncate(k);
+				return found;
+			}
+			radius = (radius * 2.).min(max_radius);
+		}
+	}
+
+	pub fn nearest_one(&self, agent_type: AgentType, p: Position, max_radius: f32) -> Option<Entry> {
+		self.nearest(agent_type, p, 1, max_radius).into_iter().next()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use core::geometry::Rect;
+	use rand::{self, Rng};
+	use test::Bencher;
+
+	const HALF_SIDE: f32 = 50.;
+
+	fn bounded() -> Topology { Topology::from_rect(Rect::new(-HALF_SIDE, -HALF_SIDE, HALF_SIDE, HALF_SIDE)) }
+
+	fn torus() -> Topology {
+		Topology::Torus {
+			left: -HALF_SIDE,
+			bottom: -HALF_SIDE,
+			right: HALF_SIDE,
+			top: HALF_SIDE,
+		}
+	}
+
+	fn index_of(topology: &Topology, positions: &[(f32, f32)]) -> SpatialIndex {
+		let mut index = SpatialIndex::new(SPATIAL_INDEX_CELL_SIZE);
+		index.reset(topology);
+		for (id, &(x, y)) in positions.iter().enumerate() {
+			index.insert(AgentType::Minion, id, Position::new(x, y));
+		}
+		index
+	}
+
+	fn ids_of(entries: &[Entry]) -> Vec<Id> {
+		let mut ids = entries.iter().map(|e| e.id).collect::<Vec<_>>();
+		ids.sort();
+		ids
+	}
+
+	#[test]
+	fn within_finds_only_agents_in_range() {
+		let index = index_of(&bounded(), &[(0., 0.), (3., 4.), (6., 0.), (-20., -20.)]);
+		let found = index.within(AgentType::Minion, Position::new(0., 0.), 5.);
+		assert_eq!(ids_of(&found), vec![0, 1]);
+	}
+
+	#[test]
+	fn within_filters_by_agent_type() {
+		let index = index_of(&bounded(), &[(0., 0.)]);
+		assert!(index.within(AgentType::Spore, Position::new(0., 0.), 5.).is_empty());
+	}
+
+	#[test]
+	fn within_wraps_around_a_torus() {
+		let positions = [(HALF_SIDE - 1., 0.), (0., -HALF_SIDE + 1.)];
+		let wrapped = index_of(&torus(), &positions);
+		let across = wrapped.within(AgentType::Minion, Position::new(-HALF_SIDE + 1., 0.), 5.);
+		assert_eq!(ids_of(&across), vec![0]);
+		let corner = wrapped.within(AgentType::Minion, Position::new(0., HALF_SIDE - 1.), 5.);
+		assert_eq!(ids_of(&corner), vec![1]);
+
+		let bounded = index_of(&bounded(), &positions);
+		assert!(
+			bounded
+				.within(AgentType::Minion, Position::new(-HALF_SIDE + 1., 0.), 5.)
+				.is_empty()
+		);
+	}
+
+	#[test]
+	fn nearest_sorts_by_distance_and_keeps_k() {
+		let index = index_of(&bounded(), &[(30., 0.), (1., 0.), (0., 12.), (-5., 0.)]);
+		let found = index.nearest(AgentType::Minion, Position::new(0., 0.), 3, 100.);
+		assert_eq!(found.iter().map(|e| e.id).collect::<Vec<_>>(), vec![1, 3, 2]);
+	}
+
+	#[test]
+	fn nearest_stops_at_max_radius() {
+		let index = index_of(&bounded(), &[(30., 0.)]);
+		assert!(index.nearest_one(AgentType::Minion, Position::new(0., 0.), 20.).is_none());
+		assert_eq!(
+			index.nearest_one(AgentType::Minion, Position::new(0., 0.), 40.).map(|e| e.id),
+			Some(0)
+		);
+	}
+
+	#[test]
+	fn nearest_wraps_around_a_torus() {
+		let index = index_of(&torus(), &[(HALF_SIDE - 2., 0.), (-HALF_SIDE + 10., 0.)]);
+		let found = index.nearest_one(AgentType::Minion, Position::new(-HALF_SIDE + 1., 0.), 20.);
+		assert_eq!(found.map(|e| e.id), Some(0));
+	}
+
+	const BENCH_MINIONS: usize = 2000;
+	const BENCH_RADIUS: f32 = 5.;
+
+	fn random_positions(n: usize) -> Vec<(f32, f32)> {
+		let mut rng = rand::thread_rng();
+		(0..n)
+			.map(|_| {
+				(
+					rng.gen_range(-HALF_SIDE, HALF_SIDE),
+					rng.gen_range(-HALF_SIDE, HALF_SIDE),
+				)
+			}).collect()
+	}
+
+	/// Every minion looking for its neighbours through the index
+	#[bench]
+	fn bench_within_index(b: &mut Bencher) {
+		let topology = torus();
+		let positions = random_positions(BENCH_MINIONS);
+		b.iter(|| {
+			let index = index_of(&topology, &positions);
+			positions
+				.iter()
+				.map(|&(x, y)| index.within(AgentType::Minion, Position::new(x, y), BENCH_RADIUS).len())
+				.sum::<usize>()
+		});
+	}
+
+	/// The same queries scanning every minion, as before the index
+	#[bench]
+	fn bench_within_linear_scan(b: &mut Bencher) {
+		let topology = torus();
+		let positions = random_positions(BENCH_MINIONS)
+			.into_iter()
+			.map(|(x, y)| Position::new(x, y))
+			.collect::<Vec<_>>();
+		let r2 = BENCH_RADIUS * BENCH_RADIUS;
+		b.iter(|| {
+			positions
+				.iter()
+				.map(|&p| {
+					positions
+						.iter()
+						.filter(|&&q| topology.delta(p, q).magnitude2() <= r2)
+						.count()
+				}).sum::<usize>()
+		});
+	}
+}
@@ -0,0 +1,74 @@
+use app::constants::*;
+use core::clock::Seconds;
+use rand;
+
+/// A disease agent, carried by one or more agents and passed on by contact
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Pathogen {
+	pub strain: u32,
+	/// energy drained from the host per second
+	pub virulence: f32,
+	/// probability of passing on the infection at each contact
+	pub transmissibility: f32,
+	/// how long the host stays infected, in seconds
+	pub duration: f32,
+}
+
+impl Pathogen {
+	pub fn random<R: rand::Rng>(rng: &mut R) -> Self {
+		Pathogen {
+			strain: rng.gen::<u32>(),
+			virulence: rng.gen_range(PATHOGEN_VIRULENCE_MIN, PATHOGEN_VIRULENCE_MAX),
+			transmissibility: rng.gen_range(PATHOGEN_TRANSMISSIBILITY_MIN, PATHOGEN_TRANSMISSIBILITY_MAX),
+			duration: rng.gen_range(PATHOGEN_DURATION_MIN, PATHOGEN_DURATION_MAX),
+		}
+	}
+
+	/// Copies the pathogen for a new host, occasionally introducing a new strain
+	pub fn replicate<R: rand::Rng>(&self, rng: &mut R) -> Self {
+		if rng.gen::<f32>() < PATHOGEN_MUTATION_RATE {
+			fn drift<R: rand::Rng>(rng: &mut R, value: f32, min: f32, max: f32) -> f32 {
+				let delta = (max - min) * PATHOGEN_MUTATION_STEP;
+				(value + rng.gen_range(-delta, delta)).max(min).min(max)
+			}
+			Pathogen {
+				strain: rng.gen::<u32>(),
+				virulence: drift(rng, self.virulence, PATHOGEN_VIRULENCE_MIN, PATHOGEN_VIRULENCE_MAX),
+				transmissibility: drift(
+					rng,
+					self.transmissibility,
+					PATHOGEN_TRANSMISSIBILITY_MIN,
+					PATHOGEN_TRANSMISSIBILITY_MAX,
+				),
+				duration: drift(rng, self.duration, PATHOGEN_DURATION_MIN, PATHOGEN_DURATION_MAX),
+			}
+		} else {
+			self.clone()
+		}
+	}
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Infection {
+	pathogen: Pathogen,
+	left: f32,
+}
+
+impl Infection {
+	pub fn new(pathogen: Pathogen) -> Self {
+		Infection {
+			left: pathogen.duration,
+			pathogen,
+		}
+	}
+
+	pub fn pathogen(&self) -> &Pathogen { &self.pathogen }
+
+	/// Fraction of the infection time still to run, 1 when just infected
+	pub fn severity(&self) -> f32 { (self.left / self.pathogen.duration).max(0.).min(1.) }
+
+	/// Hosts with a strong immune system recover faster
+	pub fn update(&mut self, dt: Seconds, immunity: f32) { self.left -= dt * (1. + immunity); }
+
+	pub fn is_over(&self) -> bool { self.left <= 0. }
+}
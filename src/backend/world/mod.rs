@@ -1,11 +1,16 @@
 pub mod agent;
 pub mod alert;
+pub mod contact;
 pub mod gen;
+pub mod index;
+pub mod infection;
 pub mod particle;
 pub mod persist;
 pub mod phen;
+pub mod scenario;
 pub mod segment;
 pub mod swarm;
+pub mod topology;
 
 use backend::obj;
 use backend::obj::*;
@@ -41,10 +46,12 @@ pub trait AgentState {
 }
 
 pub struct World {
-	pub extent: Rect,
+	topology: topology::Topology,
 	phase: Rgba,
 	swarms: HashMap<AgentType, Swarm>,
 	feeders: Vec<Feeder>,
+	scenario: scenario::Scenario,
+	index: index::SpatialIndex,
 	registered: HashSet<Id>,
 	registered_player_id: Option<Id>,
 	regenerations: usize,
@@ -52,6 +59,7 @@ pub struct World {
 	resource_gene_pool: gen::GenePool,
 	clock: SimulationTimer,
 	particles: Vec<Particle>,
+	weapon: Weapon,
 }
 
 impl AgentState for World {
@@ -63,6 +71,8 @@ pub struct Feeder {
 	transform: Transform,
 	rate: Seconds,
 	intensity: f32,
+	/// time left before the next emission, as last reported by the game system
+	countdown: Option<Seconds>,
 }
 
 impl Feeder {
@@ -71,11 +81,40 @@ impl Feeder {
 			transform: Transform::from_position(Position::new(x, y)),
 			rate,
 			intensity: 1.0,
+			countdown: None,
 		}
 	}
 	pub fn rate(&self) -> Seconds { self.rate }
 	pub fn intensity(&self) -> f32 { self.intensity }
 	pub fn set_intensity(&mut self, intensity: f32) { self.intensity = intensity }
+	pub fn countdown(&self) -> Option<Seconds> { self.countdown }
+	pub fn set_countdown(&mut self, countdown: Seconds) { self.countdown = Some(countdown) }
+}
+
+/// A single minion traded between worlds: its genes and, optionally, how grown and fed it was
+#[derive(Clone, Debug)]
+pub struct Specimen {
+	pub dna: gen::Dna,
+	pub maturity: Option<f32>,
+	pub energy: Option<f32>,
+}
+
+impl Specimen {
+	pub fn of(agent: &Agent) -> Self {
+		Specimen {
+			dna: agent.dna().clone(),
+			maturity: agent.segments().first().map(|segment| segment.state.maturity()),
+			energy: Some(agent.state.energy()),
+		}
+	}
+}
+
+/// The player's weapon, charged by the game system and kept in the world so that it survives a reload
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Weapon {
+	pub bullet_speed: f32,
+	pub firing_rate: SecondsValue,
+	pub bullet_charge: SecondsValue,
 }
 
 impl Transformable for Feeder {
@@ -95,6 +134,14 @@ impl World {
 		fn default_gene_pool(_: io::Error) -> gen::GenePool {
 			gen::GenePool::parse_from_base64(DEFAULT_MINION_GENE_POOL)
 		}
+		fn default_scenario(e: io::Error) -> scenario::Scenario {
+			warn!("Unable to load scenario: {}", e);
+			scenario::Scenario::default()
+		}
+		let scenario = res
+			.load(DEFAULT_SCENARIO_FILE)
+			.and_then(|data| scenario::Scenario::parse_from_resource(&data))
+			.unwrap_or_else(default_scenario);
 		let emitter_rate = Seconds::new(EMITTER_PERIOD);
 		let num_emitters: usize = 7;
 		let feeders = (0..num_emitters)
@@ -103,10 +150,12 @@ impl World {
 				Feeder::new(c * EMITTER_DISTANCE, s * EMITTER_DISTANCE, emitter_rate)
 			}).collect::<Vec<_>>();
 		World {
-			extent: Rect::new(-WORLD_RADIUS, -WORLD_RADIUS, WORLD_RADIUS, WORLD_RADIUS),
+			topology: scenario.topology.clone().unwrap_or_default(),
 			phase: COLOR_TRANSPARENT,
 			swarms,
 			feeders,
+			scenario,
+			index: index::SpatialIndex::default(),
 			minion_gene_pool: res
 				.load(minion_gene_pool)
 				.map(|data| gen::GenePool::parse_from_resource(&data))
@@ -117,6 +166,7 @@ impl World {
 			regenerations: 0usize,
 			clock,
 			particles: Vec::with_capacity(10000),
+			weapon: Weapon::default(),
 		}
 	}
 
@@ -127,12 +177,21 @@ impl World {
 		self.registered.clear();
 		self.registered_player_id = None;
 		self.particles.clear();
+		self.weapon = Weapon::default();
 	}
 
 	pub fn tick(&mut self, dt: Seconds) { self.clock.tick(dt); }
 
+	pub fn topology(&self) -> &topology::Topology { &self.topology }
+
+	pub fn extent(&self) -> Rect { self.topology.extent() }
+
 	pub fn seconds(&self) -> Seconds { self.clock.seconds() }
 
+	pub fn regenerations(&self) -> usize { self.regenerations }
+
+	pub fn minion_gene_pool(&self) -> &gen::GenePool { &self.minion_gene_pool }
+
 	pub fn extinctions(&self) -> usize {
 		if self.regenerations > 1 {
 			self.regenerations - 1
@@ -176,7 +235,18 @@ impl World {
 		self.register(id)
 	}
 
-	pub fn new_spore(&mut self, outbox: &Outbox, transform: Transform, dna: &gen::Dna) -> obj::Id {
+	/// Detaches a limb from an agent, the lost segments decay into resources
+	pub fn sever_limb(&mut self, outbox: &Outbox, key: agent::Key) {
+		let (severed, dna) = match self.agent_mut(key.agent_id) {
+			Some(agent) => (agent.sever(key.segment_index), agent.dna().clone()),
+			None => return,
+		};
+		for segment in severed.iter() {
+			self.decay_to_resource(outbox, segment.transform.clone(), &dna);
+		}
+	}
+
+	pub fn new_spore(&mut self, outbox: &Outbox, transform: Transform, dna: &gen::Dna, lineage: obj::Id) -> obj::Id {
 		let clock = self.clock.clone();
 		let id = self.swarm_mut(&AgentType::Spore).spawn(
 			&mut gen::Genome::copy_from(dna).mutate(&mut rand::thread_rng()),
@@ -187,6 +257,9 @@ impl World {
 			},
 			&clock,
 		);
+		if let Some(agent) = self.agent_mut(id) {
+			agent.state.descend_from(lineage);
+		}
 		let livery_color = self.agent(id).unwrap().segment(0).unwrap().livery.albedo;
 		outbox.post(Message::NewEmitter(particle::Emitter::for_new_spore(
 			transform,
@@ -196,7 +269,7 @@ impl World {
 		self.register(id)
 	}
 
-	pub fn hatch_spore(&mut self, outbox: &Outbox, transform: Transform, dna: &gen::Dna) -> obj::Id {
+	pub fn hatch_spore(&mut self, outbox: &Outbox, transform: Transform, dna: &gen::Dna, lineage: obj::Id) -> obj::Id {
 		let clock = self.clock.clone();
 		let id = self.swarm_mut(&AgentType::Minion).spawn(
 			&mut gen::Genome::copy_from(dna),
@@ -207,6 +280,9 @@ impl World {
 			},
 			&clock,
 		);
+		if let Some(agent) = self.agent_mut(id) {
+			agent.state.descend_from(lineage);
+		}
 		let livery_color = self.agent(id).unwrap().segment(0).unwrap().livery.albedo;
 		outbox.post(Message::NewEmitter(particle::Emitter::for_new_minion(
 			transform,
@@ -224,7 +300,7 @@ impl World {
 		self.regenerations += 1;
 		let n = self.minion_gene_pool.len();
 		let clock = self.clock.clone();
-		let mut r = self.extent.top_right().x * INITIAL_SPAWN_RADIUS_RATIO;
+		let mut r = self.extent().top_right().x * INITIAL_SPAWN_RADIUS_RATIO;
 		let mut angle = 0.0f32;
 		let angle_delta = consts::PI * 2. / INITIAL_SPAWN_RADIUS_SLICES as f32;
 		for _ in 0..n {
@@ -265,6 +341,17 @@ impl World {
 		self.register(id)
 	}
 
+	pub fn init_props(&mut self) {
+		let clock = self.clock.clone();
+		let props = self.scenario.props.clone();
+		for prop in &props {
+			let id = self
+				.swarm_mut(&AgentType::Prop)
+				.spawn_with(|id| phen::develop_prop(id, prop, &clock));
+			self.register(id);
+		}
+	}
+
 	pub fn get_player_agent_id(&self) -> Option<obj::Id> { self.registered_player_id }
 
 	fn get_player_segment_mut(&mut self) -> Option<&mut segment::Segment> {
@@ -328,6 +415,27 @@ impl World {
 		self.register(id)
 	}
 
+	/// Grows a minion from a specimen, as it was when exported if the specimen says so
+	pub fn spawn_specimen(&mut self, specimen: &Specimen, pos: Position) -> obj::Id {
+		let angle = consts::PI / 2. + f32::atan2(pos.y, pos.x);
+		let clock = self.clock.clone();
+		let id = self.swarm_mut(&AgentType::Minion).spawn(
+			&mut gen::Genome::copy_from(&specimen.dna),
+			agent::InitialState {
+				transform: Transform::new(pos, angle),
+				charge: DEFAULT_MINION_CHARGE,
+				maturity: specimen.maturity,
+				..Default::default()
+			},
+			&clock,
+		);
+		if let (Some(energy), Some(agent)) = (specimen.energy, self.agent_mut(id)) {
+			let delta = energy - agent.state.energy();
+			agent.state.absorb(delta);
+		}
+		self.register(id)
+	}
+
 	pub fn register(&mut self, id: obj::Id) -> obj::Id {
 		self.registered.insert(id);
 		id
@@ -361,8 +469,22 @@ impl World {
 
 	pub fn feeders(&self) -> &[Feeder] { self.feeders.as_slice() }
 
+	pub fn scenario(&self) -> &scenario::Scenario { &self.scenario }
+
+	pub fn index(&self) -> &index::SpatialIndex { &self.index }
+
+	/// Brings the spatial index up to date with the current position of all the agents
+	pub fn reindex(&mut self) {
+		self.index
+			.rebuild(&self.topology, self.swarms.iter().map(|(t, s)| (*t, s.agents())));
+	}
+
 	pub fn feeders_mut(&mut self) -> &mut [Feeder] { self.feeders.as_mut_slice() }
 
+	pub fn weapon(&self) -> Weapon { self.weapon }
+
+	pub fn set_weapon(&mut self, weapon: Weapon) { self.weapon = weapon }
+
 	pub fn swarms(&self) -> &SwarmMap { &self.swarms }
 
 	pub fn phase(&self) -> Rgba { self.phase }
@@ -385,20 +507,53 @@ impl World {
 		v.into_boxed_slice()
 	}
 
-	pub fn serialize(&self, containing_dir: &path::Path) -> io::Result<path::PathBuf> {
+	/// A file named after the current time, in a folder that is created if needed
+	pub fn snapshot_file_name(containing_dir: &path::Path, format: persist::Format) -> path::PathBuf {
 		let now: DateTime<Utc> = Utc::now();
 		fs::create_dir_all(containing_dir).is_ok();
-		let file_name = containing_dir.join(now.format(DUMP_FILE_PATTERN_JSON).to_string());
+		containing_dir.join(now.format(DUMP_FILE_PATTERN_SNAPSHOT).to_string() + format.extension())
+	}
+
+	pub fn serialize(&self, containing_dir: &path::Path, format: persist::Format) -> Result<path::PathBuf, persist::Error> {
+		let file_name = Self::snapshot_file_name(containing_dir, format);
 		persist::Serializer::save(file_name.as_path(), self)?;
 		Ok(file_name)
 	}
 
 	pub fn dump(&self, containing_dir: &path::Path) -> io::Result<path::PathBuf> {
+		self.dump_minions(containing_dir, |_| true)
+	}
+
+	/// Saves the gene pool of the selected minions only
+	pub fn dump_selected(&self, containing_dir: &path::Path) -> io::Result<path::PathBuf> {
+		self.dump_minions(containing_dir, |agent| agent.state.selected())
+	}
+
+	/// Writes one specimen file for each selected minion
+	pub fn export_selected(&self, containing_dir: &path::Path) -> Result<Vec<path::PathBuf>, persist::Error> {
+		let now: DateTime<Utc> = Utc::now();
+		fs::create_dir_all(containing_dir)?;
+		let mut exported = Vec::new();
+		for agent in self.agents(agent::AgentType::Minion).values().filter(|a| a.state.selected()) {
+			let file_name = containing_dir.join(format!(
+				"{}_{}{}",
+				now.format(DUMP_FILE_PATTERN_SNAPSHOT),
+				agent.id(),
+				persist::SPECIMEN_EXTENSION
+			));
+			persist::Serializer::save_specimen(&file_name, &Specimen::of(agent))?;
+			exported.push(file_name);
+		}
+		Ok(exported)
+	}
+
+	fn dump_minions<F>(&self, containing_dir: &path::Path, filter: F) -> io::Result<path::PathBuf>
+	where F: Fn(&Agent) -> bool {
 		let now: DateTime<Utc> = Utc::now();
 		let file_name = containing_dir.join(now.format(DUMP_FILE_PATTERN_CSV).to_string());
 		fs::create_dir_all(containing_dir).is_ok();
 		let mut f = fs::File::create(&file_name)?;
-		for (_, agent) in self.agents(agent::AgentType::Minion).iter() {
+		for agent in self.agents(agent::AgentType::Minion).values().filter(|a| filter(a)) {
 			info!("{}", agent.dna().to_base64(base64::STANDARD));
 			f.write_fmt(format_args!("{}\n", agent.dna().to_base64(base64::STANDARD)))?;
 		}
@@ -87,6 +87,7 @@ impl EmitterStyle {
 }
 
 #[allow(unused)]
+#[derive(Clone)]
 pub struct Particle {
 	transform: Transform,
 	direction: Velocity,
@@ -1,19 +1,257 @@
+use std::error;
+use std::ffi::OsStr;
+use std::fmt;
 use std::io;
+use std::io::Write;
 use std::fs;
 use std::path;
 use backend::world;
 use backend::world::agent;
 use backend::world::gen;
+use backend::world::infection;
+use backend::world::topology;
+use backend::obj::Transformable;
 use num_traits::FromPrimitive;
 use core::geometry;
 use core::clock;
 use serde_json;
+use rmp_serde;
+use flate2::Compression;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
 use serialize::base64::{self, ToBase64, FromBase64};
 
+/// Bumped whenever the layout of a snapshot changes, with a matching entry in `MIGRATIONS`
+pub const SNAPSHOT_VERSION: u32 = 2;
+
+/// How a snapshot is stored, picked from the file extension
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Format {
+	/// `.json`, readable and diffable
+	Json,
+	/// `.json.gz`
+	CompressedJson,
+	/// `.bin`, MessagePack with named fields, so it can be upgraded like JSON
+	Binary,
+}
+
+impl Default for Format {
+	fn default() -> Self { Format::Json }
+}
+
+impl Format {
+	pub fn from_path(file_path: &path::Path) -> Option<Format> {
+		let name = file_path.file_name().and_then(OsStr::to_str)?;
+		Self::all().iter().cloned().find(|format| name.ends_with(format.extension()))
+	}
+
+	/// Longest extensions first, so that `.json.gz` is not mistaken for something else
+	pub fn all() -> &'static [Format] {
+		const ALL: [Format; 3] = [Format::CompressedJson, Format::Json, Format::Binary];
+		&ALL
+	}
+
+	pub fn extension(self) -> &'static str {
+		match self {
+			Format::Json => ".json",
+			Format::CompressedJson => ".json.gz",
+			Format::Binary => ".bin",
+		}
+	}
+
+	pub fn parse(name: &str) -> Option<Format> {
+		Self::all().iter().cloned().find(|format| &format.extension()[1..] == name)
+	}
+}
+
+#[derive(Debug)]
+pub enum Error {
+	Io(io::Error),
+	Format(serde_json::Error),
+	Binary(String),
+	UnknownFormat(path::PathBuf),
+	/// the contents do not match the checksum they were saved with
+	Checksum,
+	/// well formed, but with values that make no sense
+	Invalid(String),
+	/// written by a newer build than this one
+	UnsupportedVersion(u32),
+	Migration(u32, String),
+}
+
+impl From<io::Error> for Error {
+	fn from(err: io::Error) -> Self { Error::Io(err) }
+}
+
+impl From<serde_json::Error> for Error {
+	fn from(err: serde_json::Error) -> Self { Error::Format(err) }
+}
+
+impl From<rmp_serde::encode::Error> for Error {
+	fn from(err: rmp_serde::encode::Error) -> Self { Error::Binary(err.to_string()) }
+}
+
+impl From<rmp_serde::decode::Error> for Error {
+	fn from(err: rmp_serde::decode::Error) -> Self { Error::Binary(err.to_string()) }
+}
+
+impl error::Error for Error {
+	fn description(&self) -> &str {
+		match *self {
+			Error::Io(ref e) => error::Error::description(e),
+			Error::Format(ref e) => error::Error::description(e),
+			Error::Binary(ref reason) => reason,
+			Error::UnknownFormat(_) => "unknown snapshot format",
+			Error::Checksum => "snapshot checksum mismatch",
+			Error::Invalid(ref reason) => reason,
+			Error::UnsupportedVersion(_) => "snapshot version not supported",
+			Error::Migration(_, ref reason) => reason,
+		}
+	}
+}
+
+impl fmt::Display for Error {
+	fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+		match *self {
+			Error::Io(ref e) => write!(f, "cannot read snapshot: {}", e),
+			Error::Format(ref e) => write!(f, "malformed snapshot: {}", e),
+			Error::Binary(ref reason) => write!(f, "malformed binary snapshot: {}", reason),
+			Error::UnknownFormat(ref file_path) => write!(
+				f,
+				"cannot tell the format of {:?}, expected one of .json, .json.gz or .bin",
+				file_path
+			),
+			Error::Checksum => write!(f, "snapshot is corrupted, its checksum does not match"),
+			Error::Invalid(ref reason) => write!(f, "invalid contents: {}", reason),
+			Error::UnsupportedVersion(version) => write!(
+				f,
+				"snapshot version {} is newer than the supported version {}",
+				version, SNAPSHOT_VERSION
+			),
+			Error::Migration(version, ref reason) => {
+				write!(f, "cannot upgrade snapshot from version {}: {}", version, reason)
+			}
+		}
+	}
+}
+
+type Migration = fn(&mut serde_json::Map<String, serde_json::Value>) -> Result<(), String>;
+
+/// Upgrades a snapshot from version `i` to `i + 1`, applied in sequence until the current version
+const MIGRATIONS: [Migration; SNAPSHOT_VERSION as usize] = [migrate_unversioned, migrate_segment_indices];
+
+/// Snapshots written before versioning have no version field, and
+/// fields added since then are already optional
+fn migrate_unversioned(_: &mut serde_json::Map<String, serde_json::Value>) -> Result<(), String> { Ok(()) }
+
+/// Version 2 matches segments by index and lists the limbs severed from each agent.
+/// Earlier versions saved every segment in order, and never lost a limb on load
+fn migrate_segment_indices(fields: &mut serde_json::Map<String, serde_json::Value>) -> Result<(), String> {
+	let swarms = fields
+		.get_mut("swarms")
+		.and_then(|swarms| swarms.as_array_mut())
+		.ok_or_else(|| "missing swarms".to_owned())?;
+	for swarm in swarms {
+		let agents = swarm
+			.get_mut("agents")
+			.and_then(|agents| agents.as_array_mut())
+			.ok_or_else(|| "swarm without agents".to_owned())?;
+		for agent in agents {
+			let agent = agent.as_object_mut().ok_or_else(|| "agent is not an object".to_owned())?;
+			agent.insert("severed".to_owned(), serde_json::Value::Array(Vec::new()));
+			let segments = agent
+				.get_mut("segments")
+				.and_then(|segments| segments.as_array_mut())
+				.ok_or_else(|| "agent without segments".to_owned())?;
+			for (index, segment) in segments.iter_mut().enumerate() {
+				let segment = segment.as_object_mut().ok_or_else(|| "segment is not an object".to_owned())?;
+				segment.insert("index".to_owned(), serde_json::Value::from(index));
+			}
+		}
+	}
+	Ok(())
+}
+
+/// Follows the payload on a line of its own, in every format
+const CHECKSUM_TAG: &[u8] = b"\nfnv1a64 ";
+const CHECKSUM_DIGITS: usize = 16;
+
+/// FNV-1a over the payload exactly as it is stored
+fn checksum_of(payload: &[u8]) -> String {
+	let hash = payload
+		.iter()
+		.fold(0xcbf2_9ce4_8422_2325u64, |hash, b| (hash ^ u64::from(*b)).wrapping_mul(0x0100_0000_01b3));
+	format!("{:016x}", hash)
+}
+
+/// Appends the checksum of the payload
+fn seal(mut payload: Vec<u8>) -> Vec<u8> {
+	let checksum = checksum_of(&payload);
+	payload.extend_from_slice(CHECKSUM_TAG);
+	payload.extend_from_slice(checksum.as_bytes());
+	payload.push(b'\n');
+	payload
+}
+
+/// Splits the payload from its checksum and checks that they match.
+/// Snapshots saved before checksums were introduced are trusted
+fn verify(contents: &[u8]) -> Result<&[u8], Error> {
+	let trailer_len = CHECKSUM_TAG.len() + CHECKSUM_DIGITS + 1;
+	if contents.len() < trailer_len {
+		return Ok(contents);
+	}
+	let (payload, trailer) = contents.split_at(contents.len() - trailer_len);
+	if !trailer.starts_with(CHECKSUM_TAG) || trailer.last() != Some(&b'\n') {
+		return Ok(contents);
+	}
+	let stored = &trailer[CHECKSUM_TAG.len()..trailer_len - 1];
+	if stored == checksum_of(payload).as_bytes() {
+		Ok(payload)
+	} else {
+		Err(Error::Checksum)
+	}
+}
+
+fn migrate(mut value: serde_json::Value) -> Result<World, Error> {
+	{
+		let fields = value
+			.as_object_mut()
+			.ok_or_else(|| Error::Migration(0, "not a JSON object".to_owned()))?;
+		let mut version = match fields.get("version") {
+			None => 0,
+			Some(v) => v
+				.as_u64()
+				.map(|v| v as u32)
+				.ok_or_else(|| Error::Migration(0, format!("invalid version {}", v)))?,
+		};
+		if version > SNAPSHOT_VERSION {
+			return Err(Error::UnsupportedVersion(version));
+		}
+		if version < SNAPSHOT_VERSION {
+			info!("Upgrading snapshot from version {} to {}", version, SNAPSHOT_VERSION);
+		}
+		while version < SNAPSHOT_VERSION {
+			MIGRATIONS[version as usize](fields).map_err(|reason| Error::Migration(version, reason))?;
+			version += 1;
+			fields.insert("version".to_owned(), serde_json::Value::from(version));
+		}
+	}
+	Ok(serde_json::from_value(value)?)
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 pub struct Segment {
+	index: u8,
 	charge: f32,
 	target_charge: f32,
+	#[serde(default)]
+	maturity: Option<f32>,
+	/// position and angle, older snapshots only have the ones of the first segment
+	#[serde(default)]
+	transform: Option<(f32, f32, f32)>,
+	/// velocity and spin
+	#[serde(default)]
+	motion: Option<(f32, f32, f32)>,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -32,6 +270,23 @@ struct Agent {
 	maturity: f32,
 	phase: f32,
 	energy: f32,
+	#[serde(default)]
+	lifecycle_left: Option<f64>,
+	#[serde(default)]
+	infection: Option<infection::Infection>,
+	#[serde(default)]
+	foreign_dna: Option<String>,
+	#[serde(default)]
+	target: Option<usize>,
+	#[serde(default)]
+	target_position: Option<(f32, f32)>,
+	#[serde(default)]
+	lineage: Option<usize>,
+	/// most recent first
+	#[serde(default)]
+	trajectory: Vec<(f32, f32)>,
+	/// replayed in order on the developed body before the segments are restored
+	severed: Vec<u8>,
 	segments: Vec<Segment>,
 }
 
@@ -42,33 +297,87 @@ pub struct Swarm {
 	agents: Vec<Agent>,
 }
 
+#[derive(Serialize, Deserialize, Debug)]
+struct Feeder {
+	x: f32,
+	y: f32,
+	angle: f32,
+	rate: f64,
+	intensity: f32,
+	countdown: Option<f64>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+struct Weapon {
+	bullet_speed: f32,
+	firing_rate: f64,
+	bullet_charge: f64,
+}
+
+/// Current version of specimen files, which are not upgraded: older versions are read as they are
+pub const SPECIMEN_VERSION: u32 = 1;
+pub const SPECIMEN_EXTENSION: &str = ".specimen";
+
+#[derive(Serialize, Deserialize, Debug)]
+struct Specimen {
+	version: u32,
+	dna: String,
+	#[serde(default)]
+	maturity: Option<f32>,
+	#[serde(default)]
+	energy: Option<f32>,
+}
+
+/// Particles and their emitters are not saved: they are purely cosmetic and fade within a few seconds,
+/// so a restored world starts without any
 #[derive(Serialize, Deserialize, Debug)]
 pub struct World {
+	version: u32,
 	left: f32,
 	bottom: f32,
 	right: f32,
 	top: f32,
+	#[serde(default)]
+	topology: Option<topology::Topology>,
 	swarms: Vec<Swarm>,
 	regenerations: usize,
 	minion_gene_pool: Vec<String>,
 	minion_gene_pool_index: usize,
 	resource_gene_pool: Vec<String>,
 	resource_gene_pool_index: usize,
+	#[serde(default)]
+	clock_seconds: f64,
+	/// older snapshots keep the emitters of a new world
+	#[serde(default)]
+	feeders: Vec<Feeder>,
+	#[serde(default)]
+	weapon: Weapon,
+}
+
+/// Headline figures of a saved world
+#[derive(Clone, Debug)]
+pub struct Contents {
+	pub seconds: f64,
+	pub population: usize,
+	pub extinctions: usize,
 }
 
 pub struct Serializer;
 
 impl Serializer {
 	pub fn save_snapshot(world: &world::World) -> World {
-		fn serialize_swarm(src: &world::swarm::Swarm) -> Swarm {
+		fn serialize_swarm(src: &world::swarm::Swarm, timer: &clock::SimulationTimer) -> Swarm {
+			// in id order, so that saving the same world twice gives the same snapshot
+			let mut agents: Vec<_> = src.agents().iter().map(|(_k, v)| serialize_agent(v, timer)).collect();
+			agents.sort_by_key(|agent| agent.id);
 			Swarm {
 				seq: src.seq() as usize,
 				agent_type: src.agent_type() as usize,
-				agents: src.agents().iter().map(|(_k, v)| serialize_agent(v)).collect(),
+				agents,
 			}
 		}
 
-		fn serialize_agent(src: &world::agent::Agent) -> Agent {
+		fn serialize_agent(src: &world::agent::Agent, timer: &clock::SimulationTimer) -> Agent {
 			let body = &src.segments[0];
 
 			Agent {
@@ -86,21 +395,48 @@ impl Serializer {
 				flags: src.state.flags().bits(),
 				phase: src.state.phase(),
 				energy: src.state.energy(),
+				lifecycle_left: Some(src.state.lifecycle().left(timer).get()),
+				infection: src.state.infection().cloned(),
+				foreign_dna: src.state.foreign_dna().as_ref().map(|dna| dna.to_base64(base64::STANDARD)),
+				target: *src.state.target(),
+				target_position: Some((src.state.target_position().x, src.state.target_position().y)),
+				lineage: src.state.lineage(),
+				trajectory: src.state.trajectory().iter().map(|p| (p.x, p.y)).collect(),
+				severed: src.severed().to_vec(),
 				segments: src.segments().iter().map(|s| serialize_segment(s)).collect(),
 			}
 		}
 
 		fn serialize_segment(src: &world::segment::Segment) -> Segment {
 			Segment {
+				index: src.index,
 				charge: src.state.charge(),
 				target_charge: src.state.target_charge(),
+				maturity: Some(src.state.maturity()),
+				transform: Some((src.transform.position.x, src.transform.position.y, src.transform.angle)),
+				motion: Some((src.motion.velocity.x, src.motion.velocity.y, src.motion.spin)),
+			}
+		}
+
+		fn serialize_feeder(src: &world::Feeder) -> Feeder {
+			let transform = src.transform();
+			Feeder {
+				x: transform.position.x,
+				y: transform.position.y,
+				angle: transform.angle,
+				rate: src.rate().get(),
+				intensity: src.intensity(),
+				countdown: src.countdown().map(|countdown| countdown.get()),
 			}
 		}
 
-		let swarms = world.swarms()
+		// props are rebuilt from the scenario, no need to save them
+		let mut swarms: Vec<_> = world.swarms()
 			.iter()
-			.map(|(_k, v)| serialize_swarm(v))
+			.filter(|&(k, _v)| *k != agent::AgentType::Prop)
+			.map(|(_k, v)| serialize_swarm(v, &world.clock))
 			.collect();
+		swarms.sort_by_key(|swarm| swarm.agent_type);
 		let minion_gene_pool: Vec<_> = world.minion_gene_pool
 			.gene_pool_iter()
 			.map(|dna| dna.to_base64(base64::STANDARD))
@@ -109,27 +445,57 @@ impl Serializer {
 			.gene_pool_iter()
 			.map(|dna| dna.to_base64(base64::STANDARD))
 			.collect();
+		let extent = world.extent();
 		World {
-			left: world.extent.min.x,
-			bottom: world.extent.min.y,
-			right: world.extent.max.x,
-			top: world.extent.max.y,
+			version: SNAPSHOT_VERSION,
+			left: extent.min.x,
+			bottom: extent.min.y,
+			right: extent.max.x,
+			top: extent.max.y,
+			topology: Some(world.topology.clone()),
 			swarms,
 			regenerations: world.regenerations,
 			minion_gene_pool,
 			minion_gene_pool_index: world.minion_gene_pool.gene_pool_index(),
 			resource_gene_pool,
 			resource_gene_pool_index: world.resource_gene_pool.gene_pool_index(),
+			clock_seconds: world.clock.seconds().get(),
+			feeders: world.feeders().iter().map(serialize_feeder).collect(),
+			weapon: Weapon {
+				bullet_speed: world.weapon.bullet_speed,
+				firing_rate: world.weapon.firing_rate,
+				bullet_charge: world.weapon.bullet_charge,
+			},
 		}
 	}
 
 	pub fn restore_snapshot(src: &World, world: &mut world::World) {
+		world.clock = clock::SimulationTimer::from(clock::seconds(src.clock_seconds));
 		let timer = world.clock.clone();
-		world.extent.min.x = src.left;
-		world.extent.min.y = src.bottom;
-		world.extent.max.x = src.right;
-		world.extent.max.y = src.top;
+		// older snapshots only have the bounding rectangle
+		world.topology = src.topology.clone().unwrap_or_else(|| {
+			topology::Topology::from_rect(geometry::Rect::new(src.left, src.bottom, src.right, src.top))
+		});
 		world.regenerations = src.regenerations;
+		if !src.feeders.is_empty() {
+			world.feeders = src.feeders
+				.iter()
+				.map(|src_feeder| {
+					let mut feeder = world::Feeder::new(src_feeder.x, src_feeder.y, clock::seconds(src_feeder.rate));
+					feeder.transform_to(geometry::Transform::from_components(src_feeder.x, src_feeder.y, src_feeder.angle));
+					feeder.set_intensity(src_feeder.intensity);
+					if let Some(countdown) = src_feeder.countdown {
+						feeder.set_countdown(clock::seconds(countdown));
+					}
+					feeder
+				})
+				.collect();
+		}
+		world.weapon = world::Weapon {
+			bullet_speed: src.weapon.bullet_speed,
+			firing_rate: src.weapon.firing_rate,
+			bullet_charge: src.weapon.bullet_charge,
+		};
 
 		world.minion_gene_pool.populate_from_base64(&src.minion_gene_pool, src.minion_gene_pool_index);
 		world.resource_gene_pool.populate_from_base64(&src.resource_gene_pool, src.resource_gene_pool_index);
@@ -151,9 +517,42 @@ impl Serializer {
 						}, &timer);
 						if let Some(agent) = swarm.get_mut(id) {
 							agent.state.restore(src_agent.flags, src_agent.phase, src_agent.energy);
+							if let Some(lifecycle_left) = src_agent.lifecycle_left {
+								agent.state.restore_lifecycle(clock::seconds(lifecycle_left), &timer);
+							}
+							agent.state.restore_infection(src_agent.infection.clone());
+							if let Some(Ok(foreign_dna)) = src_agent.foreign_dna.as_ref().map(|dna| dna.from_base64()) {
+								agent.state.fertilise(&foreign_dna.into_boxed_slice());
+							}
+							if let Some((x, y)) = src_agent.target_position {
+								agent.state.retarget(src_agent.target, geometry::Position::new(x, y));
+							}
+							if let Some(lineage) = src_agent.lineage {
+								agent.state.descend_from(lineage);
+							}
+							// oldest first, so that the most recent position ends up on top
+							for &(x, y) in src_agent.trajectory.iter().rev() {
+								agent.state.track_position(geometry::Position::new(x, y));
+							}
 
-							for (src_segment, dest_segment) in src_agent.segments.iter().zip(agent.segments_mut().iter_mut()) {
+							for &index in &src_agent.severed {
+								agent.sever(index);
+							}
+							for src_segment in &src_agent.segments {
+								let dest_segment = match agent.segment_mut(src_segment.index) {
+									Some(dest_segment) => dest_segment,
+									None => continue,
+								};
 								dest_segment.state.restore(src_segment.charge, src_segment.target_charge);
+								if let Some(maturity) = src_segment.maturity {
+									dest_segment.state.set_maturity(maturity);
+								}
+								if let Some((x, y, angle)) = src_segment.transform {
+									dest_segment.transform = geometry::Transform::from_components(x, y, angle);
+								}
+								if let Some((vx, vy, spin)) = src_segment.motion {
+									dest_segment.motion = geometry::Motion::from_components(vx, vy, spin);
+								}
 							};
 							registered.push(id);
 						}
@@ -169,28 +568,347 @@ impl Serializer {
 	}
 
 	#[allow(unused)]
-	pub fn from_string(source: &str, dest: &mut world::World) -> Result<(), serde_json::Error> {
-		let result: Result<World, _> = serde_json::from_str(source);
-		match result {
-			Ok(src) => {
-				Self::restore_snapshot(&src, dest);
-				Ok(())
+	pub fn from_string(source: &str, dest: &mut world::World) -> Result<(), Error> {
+		let value = serde_json::from_slice(verify(source.as_bytes())?)?;
+		let src = migrate(value)?;
+		Self::restore_snapshot(&src, dest);
+		Ok(())
+	}
+
+	fn format_of(file_path: &path::Path) -> Result<Format, Error> {
+		Format::from_path(file_path).ok_or_else(|| Error::UnknownFormat(file_path.to_owned()))
+	}
+
+	fn encode(format: Format, s_world: &World) -> Result<Vec<u8>, Error> {
+		let mut payload = Vec::new();
+		match format {
+			Format::Json => serde_json::to_writer_pretty(&mut payload, s_world)?,
+			Format::CompressedJson => {
+				let mut encoder = GzEncoder::new(&mut payload, Compression::default());
+				serde_json::to_writer(&mut encoder, s_world)?;
+				encoder.finish()?;
 			}
-			Err(e) => Err(e)
+			Format::Binary => rmp_serde::encode::write_named(&mut payload, s_world)?,
 		}
+		Ok(payload)
 	}
 
-	pub fn save(file_path: &path::Path, world: &world::World) -> io::Result<()> {
-		let out_file = fs::File::create(file_path)?;
-		let s_world = Self::save_snapshot(world);
-		serde_json::to_writer_pretty(out_file, &s_world)?;
-		Ok(())
+	/// Anything that can be upgraded, whatever the format
+	fn decode(format: Format, payload: &[u8]) -> Result<serde_json::Value, Error> {
+		Ok(match format {
+			Format::Json => serde_json::from_slice(payload)?,
+			Format::CompressedJson => serde_json::from_reader(GzDecoder::new(payload))?,
+			Format::Binary => rmp_serde::from_read(payload)?,
+		})
 	}
 
-	pub fn load(file_path: &path::Path, world: &mut world::World) -> io::Result<()> {
-		let in_file = fs::File::open(file_path)?;
-		let src = serde_json::from_reader(in_file)?;
+	/// Written next to the destination and then renamed over it,
+	/// so that an interrupted save never leaves a truncated file behind
+	fn write_atomically(file_path: &path::Path, contents: &[u8]) -> Result<(), Error> {
+		fn write_synced(file_path: &path::Path, contents: &[u8]) -> io::Result<()> {
+			let mut out_file = fs::File::create(file_path)?;
+			out_file.write_all(contents)?;
+			out_file.sync_all()
+		}
+		let mut temp_name = file_path.file_name().map(|name| name.to_owned()).unwrap_or_default();
+		temp_name.push(".tmp");
+		let temp_path = file_path.with_file_name(temp_name);
+		let result = write_synced(&temp_path, contents).and_then(|_| fs::rename(&temp_path, file_path));
+		if result.is_err() {
+			fs::remove_file(&temp_path).is_ok();
+		}
+		result.map_err(Error::from)
+	}
+
+	/// The snapshot can be taken first and written later on another thread
+	pub fn write(file_path: &path::Path, s_world: &World) -> Result<(), Error> {
+		let format = Self::format_of(file_path)?;
+		Self::write_atomically(file_path, &seal(Self::encode(format, s_world)?))
+	}
+
+	/// Older snapshots are upgraded to the current version as they are read, whatever the format
+	pub fn read(file_path: &path::Path) -> Result<World, Error> {
+		let format = Self::format_of(file_path)?;
+		let contents = fs::read(file_path)?;
+		let value = Self::decode(format, verify(&contents)?)?;
+		migrate(value)
+	}
+
+	pub fn save(file_path: &path::Path, world: &world::World) -> Result<(), Error> {
+		Self::write(file_path, &Self::save_snapshot(world))
+	}
+
+	pub fn load(file_path: &path::Path, world: &mut world::World) -> Result<(), Error> {
+		let src = Self::read(file_path)?;
 		Self::restore_snapshot(&src, world);
 		Ok(())
 	}
+
+	/// Reads the headline figures of a snapshot, without restoring it into a world
+	pub fn peek(file_path: &path::Path) -> Result<Contents, Error> {
+		let src = Self::read(file_path)?;
+		let minion_type = agent::AgentType::Minion as usize;
+		Ok(Contents {
+			seconds: src.clock_seconds,
+			population: src
+				.swarms
+				.iter()
+				.filter(|swarm| swarm.agent_type == minion_type)
+				.map(|swarm| swarm.agents.len())
+				.sum(),
+			extinctions: if src.regenerations > 1 { src.regenerations - 1 } else { 0 },
+		})
+	}
+
+	pub fn save_specimen(file_path: &path::Path, specimen: &world::Specimen) -> Result<(), Error> {
+		let contents = serde_json::to_vec_pretty(&Specimen {
+			version: SPECIMEN_VERSION,
+			dna: specimen.dna.to_base64(base64::STANDARD),
+			maturity: specimen.maturity,
+			energy: specimen.energy,
+		})?;
+		Self::write_atomically(file_path, &contents)
+	}
+
+	pub fn load_specimen(file_path: &path::Path) -> Result<world::Specimen, Error> {
+		let in_file = io::BufReader::new(fs::File::open(file_path)?);
+		let src: Specimen = serde_json::from_reader(in_file)?;
+		if src.version > SPECIMEN_VERSION {
+			return Err(Error::UnsupportedVersion(src.version));
+		}
+		let dna = src.dna
+			.from_base64()
+			.map_err(|e| Error::Invalid(format!("invalid DNA: {}", e)))?;
+		match src.maturity {
+			Some(maturity) if !(maturity > 0. && maturity <= 1.) => {
+				return Err(Error::Invalid(format!("maturity out of range: {}", maturity)));
+			}
+			_ => {}
+		}
+		match src.energy {
+			Some(energy) if !(energy.is_finite() && energy >= 0.) => {
+				return Err(Error::Invalid(format!("invalid energy: {}", energy)));
+			}
+			_ => {}
+		}
+		Ok(world::Specimen {
+			dna: dna.into_boxed_slice(),
+			maturity: src.maturity,
+			energy: src.energy,
+		})
+	}
+
+	/// Rewrites a snapshot in the format of the destination file, upgrading it on the way
+	pub fn convert(from_path: &path::Path, to_path: &path::Path) -> Result<(), Error> {
+		let src = Self::read(from_path)?;
+		Self::write(to_path, &src)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use backend::messagebus::PubSub;
+	use backend::testing::{new_world, populated_world, temp_path};
+	use rand;
+
+	/// A populated world where something is going on: a fertilised spore, an infected minion
+	/// chasing another, a charged weapon and a feeder about to emit
+	fn eventful_world() -> world::World {
+		let mut world = populated_world();
+		let mut ids: Vec<_> = world.agents(agent::AgentType::Minion).keys().cloned().collect();
+		ids.sort();
+		let (hunter, prey) = (ids[0], ids[1]);
+		let dna = world.agent(prey).unwrap().dna().clone();
+		let transform = world.agent(hunter).unwrap().segments()[0].transform.clone();
+		let spore = world.new_spore(&PubSub::new(), transform, &dna, hunter);
+		world.agent_mut(spore).unwrap().state.fertilise(&dna);
+		{
+			let agent = world.agent_mut(hunter).unwrap();
+			agent.state.infect(infection::Pathogen::random(&mut rand::thread_rng()));
+			agent.state.retarget(Some(prey), geometry::Position::new(10., 20.));
+			for i in 0..3 {
+				agent.state.track_position(geometry::Position::new(i as f32, -i as f32));
+			}
+		}
+		world.weapon.bullet_charge = 0.5;
+		world.feeders_mut()[0].set_countdown(clock::seconds(2.5));
+		world
+	}
+
+	fn to_value(s_world: &World) -> serde_json::Value { serde_json::to_value(s_world).unwrap() }
+
+	/// Times left are stored as an end time on the clock, so they may not come back bit for bit
+	fn assert_close(expected: &serde_json::Value, actual: &serde_json::Value, at: &str) {
+		use serde_json::Value;
+		match (expected, actual) {
+			(&Value::Number(ref e), &Value::Number(ref a)) => {
+				let (e, a) = (e.as_f64().unwrap(), a.as_f64().unwrap());
+				assert!((e - a).abs() <= 1e-6 * e.abs().max(1.), "{}: {} != {}", at, e, a);
+			}
+			(&Value::Array(ref e), &Value::Array(ref a)) => {
+				assert_eq!(e.len(), a.len(), "{}", at);
+				for (i, (e, a)) in e.iter().zip(a.iter()).enumerate() {
+					assert_close(e, a, &format!("{}[{}]", at, i));
+				}
+			}
+			(&Value::Object(ref e), &Value::Object(ref a)) => {
+				assert_eq!(e.keys().collect::<Vec<_>>(), a.keys().collect::<Vec<_>>(), "{}", at);
+				for (key, e) in e {
+					assert_close(e, &a[key], &format!("{}.{}", at, key));
+				}
+			}
+			_ => assert_eq!(expected, actual, "{}", at),
+		}
+	}
+
+	#[test]
+	fn save_load_save_gives_the_same_snapshot() {
+		let world = eventful_world();
+		let snapshot = Serializer::save_snapshot(&world);
+		let agents = || snapshot.swarms.iter().flat_map(|swarm| swarm.agents.iter());
+		assert!(agents().any(|agent| agent.foreign_dna.is_some()));
+		assert!(agents().any(|agent| agent.infection.is_some()));
+		assert!(agents().any(|agent| agent.target.is_some() && !agent.trajectory.is_empty()));
+		assert!(snapshot.weapon.bullet_charge > 0.);
+		assert!(snapshot.feeders.iter().any(|feeder| feeder.countdown.is_some()));
+		let saved = to_value(&snapshot);
+		for &format in Format::all() {
+			let file_path = temp_path(&format!("round_trip{}", format.extension()));
+			Serializer::save(&file_path, &world).unwrap();
+			let mut restored = new_world();
+			let loaded = Serializer::load(&file_path, &mut restored);
+			fs::remove_file(&file_path).unwrap();
+			loaded.unwrap();
+			assert_close(&saved, &to_value(&Serializer::save_snapshot(&restored)), format.extension());
+		}
+	}
+
+	#[test]
+	fn corrupted_snapshots_are_rejected() {
+		let world = populated_world();
+		for &format in Format::all() {
+			let file_path = temp_path(&format!("corrupted{}", format.extension()));
+			Serializer::save(&file_path, &world).unwrap();
+			let mut contents = fs::read(&file_path).unwrap();
+			let middle = contents.len() / 2;
+			contents[middle] ^= 0x20;
+			fs::write(&file_path, &contents).unwrap();
+			let result = Serializer::read(&file_path);
+			fs::remove_file(&file_path).unwrap();
+			match result {
+				Err(Error::Checksum) => {}
+				other => panic!("{}: unexpected {:?}", format.extension(), other.map(|_| ())),
+			}
+		}
+	}
+
+	#[test]
+	fn the_checksum_covers_the_stored_payload() {
+		let s_world = Serializer::save_snapshot(&populated_world());
+		for &format in Format::all() {
+			let payload = Serializer::encode(format, &s_world).unwrap();
+			assert_eq!(payload.as_slice(), verify(&seal(payload.clone())).unwrap());
+			// snapshots saved before checksums are read as they are
+			assert_eq!(payload.as_slice(), verify(&payload).unwrap());
+		}
+	}
+
+	#[test]
+	fn specimens_survive_a_round_trip() {
+		let world = populated_world();
+		let agent = world.agents(agent::AgentType::Minion).values().next().unwrap();
+		let specimen = world::Specimen {
+			dna: agent.dna().clone(),
+			maturity: Some(0.75),
+			energy: Some(12.5),
+		};
+		let file_path = temp_path(&format!("round_trip{}", SPECIMEN_EXTENSION));
+		Serializer::save_specimen(&file_path, &specimen).unwrap();
+		let loaded = Serializer::load_specimen(&file_path);
+		fs::remove_file(&file_path).unwrap();
+		let loaded = loaded.unwrap();
+		assert_eq!(specimen.dna, loaded.dna);
+		assert_eq!(specimen.maturity, loaded.maturity);
+		assert_eq!(specimen.energy, loaded.energy);
+	}
+
+	#[test]
+	fn specimens_out_of_range_are_rejected() {
+		let world = populated_world();
+		let agent = world.agents(agent::AgentType::Minion).values().next().unwrap();
+		let dna = agent.dna().to_base64(base64::STANDARD);
+		let file_path = temp_path(&format!("out_of_range{}", SPECIMEN_EXTENSION));
+		let cases = [("0", "10"), ("1.5", "10"), ("-0.5", "10"), ("0.5", "-1"), ("0.5", "1e99"), ("1e99", "10")];
+		let results: Vec<_> = cases
+			.iter()
+			.map(|&(maturity, energy)| {
+				let contents = format!(
+					r#"{{"version":{},"dna":"{}","maturity":{},"energy":{}}}"#,
+					SPECIMEN_VERSION, dna, maturity, energy
+				);
+				fs::write(&file_path, contents).unwrap();
+				Serializer::load_specimen(&file_path)
+			}).collect();
+		fs::remove_file(&file_path).unwrap();
+		for (result, case) in results.into_iter().zip(cases.iter()) {
+			match result {
+				Err(Error::Invalid(_)) => {}
+				_ => panic!("specimen with maturity {} and energy {} was accepted", case.0, case.1),
+			}
+		}
+	}
+
+	#[test]
+	fn severed_limbs_stay_severed() {
+		let mut world = populated_world();
+		let id = world
+			.agents(agent::AgentType::Minion)
+			.values()
+			.find(|agent| agent.segments().len() > 2)
+			.map(|agent| agent.id())
+			.unwrap();
+		world.agent_mut(id).unwrap().sever(1);
+
+		let mut restored = new_world();
+		Serializer::restore_snapshot(&Serializer::save_snapshot(&world), &mut restored);
+		let (before, after) = (world.agent(id).unwrap(), restored.agent(id).unwrap());
+		assert_eq!(before.severed(), after.severed());
+		assert_eq!(before.segments().len(), after.segments().len());
+		for (b, a) in before.segments().iter().zip(after.segments().iter()) {
+			assert_eq!(b.index, a.index);
+			assert_eq!(
+				b.attached_to.map(|attachment| attachment.index),
+				a.attached_to.map(|attachment| attachment.index)
+			);
+			assert_eq!(b.transform.position, a.transform.position);
+		}
+	}
+
+	#[test]
+	fn version_1_snapshots_are_upgraded() {
+		let saved = to_value(&Serializer::save_snapshot(&populated_world()));
+		let mut value = saved.clone();
+		value["version"] = serde_json::Value::from(1);
+		for swarm in value["swarms"].as_array_mut().unwrap() {
+			for agent in swarm["agents"].as_array_mut().unwrap() {
+				agent.as_object_mut().unwrap().remove("severed");
+				for segment in agent["segments"].as_array_mut().unwrap() {
+					segment.as_object_mut().unwrap().remove("index");
+				}
+			}
+		}
+		assert!(serde_json::from_value::<World>(value.clone()).is_err());
+		assert_close(&saved, &to_value(&migrate(value).unwrap()), "world");
+	}
+
+	#[test]
+	fn newer_snapshots_are_rejected() {
+		let mut value = to_value(&Serializer::save_snapshot(&new_world()));
+		value["version"] = serde_json::Value::from(SNAPSHOT_VERSION + 1);
+		match migrate(value) {
+			Err(Error::UnsupportedVersion(version)) => assert_eq!(SNAPSHOT_VERSION + 1, version),
+			other => panic!("unexpected {:?}", other.map(|_| ())),
+		}
+	}
 }
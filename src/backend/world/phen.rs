@@ -1,5 +1,6 @@
 use backend::obj::*;
 use std::f32::consts;
+use std::sync::Arc;
 use app::constants::*;
 use core::color;
 use core::color::ToRgb;
@@ -15,6 +16,7 @@ use backend::world::agent::Agent;
 use backend::world::agent::Brain;
 use backend::world::agent::TypedBrain;
 use backend::world::gen::*;
+use backend::world::scenario;
 use cgmath;
 use cgmath::InnerSpace;
 
@@ -75,6 +77,7 @@ impl Phenotype for Player {
 				restitution: RESTITUTION_PLAYER,
 				friction: FRICTION_PLAYER,
 				linear_damping: LINEAR_DAMPING_PLAYER,
+				fluid_drag: FLUID_DRAG_PLAYER,
 				..Default::default()
 			},
 			Livery {
@@ -214,6 +217,15 @@ impl Phenotype for Minion {
 			}
 		}
 		let leg_shape = gen.star();
+		builder.immunity(gen.next_float(0., IMMUNITY_MAX));
+		let mut weights_touch = [[0.; N_WEIGHTS]; N_WEIGHTS];
+		for row in weights_touch.iter_mut() {
+			for weight in row.iter_mut() {
+				*weight = gen.next_float(-4., 4.);
+			}
+		}
+		builder.weights_touch(&weights_touch);
+		builder.joint_strength(gen.next_float(JOINT_STRENGTH_MIN, JOINT_STRENGTH_MAX));
 		builder
 			.addr(
 				belly,
@@ -256,20 +268,54 @@ impl Phenotype for Spore {
 			gen.dna_cloned(),
 			segment::State::with_charge(charge, charge, seconds(DEFAULT_CHARGE_DECAY_TIME)),
 		);
+		let shape = gen.ball();
+		let duration = gen.next_float(SPORE_DORMANCY_MIN, SPORE_DORMANCY_MAX);
+		let window = gen.next_float(SPORE_WINDOW_MIN, SPORE_WINDOW_MAX);
 		builder
 			.maturity(initial_state.maturity.unwrap_or(MATURITY_DEFAULT))
 			.gender(gender)
-			.start(initial_state.transform, initial_state.motion, &gen.ball())
+			.dormancy(agent::Dormancy {
+				duration: seconds(duration),
+				window: seconds(window),
+				drift: gen.next_float(0., SPORE_DRIFT_MAX),
+				food_trigger: gen.next_float(0., SPORE_FOOD_TRIGGER_MAX),
+			}).start(initial_state.transform, initial_state.motion, &shape)
 			.build(timer)
 	}
 }
 
+pub fn develop_prop(id: Id, prop: &scenario::Prop, timer: &Timer) -> agent::Agent {
+	let albedo = color::YPbPr::new(0.3, 0., 0.);
+	let flags = if prop.is_kinematic() { Flags::KINEMATIC } else { Flags::STATIC };
+	let mut builder = AgentBuilder::new(
+		id,
+		Material {
+			density: DENSITY_PROP,
+			restitution: RESTITUTION_PROP,
+			friction: FRICTION_PROP,
+			..Default::default()
+		},
+		Livery {
+			albedo: albedo.to_rgba(),
+			..Default::default()
+		},
+		Vec::new().into_boxed_slice(),
+		segment::State::with_charge(PROP_CHARGE, PROP_CHARGE, seconds(DEFAULT_CHARGE_DECAY_TIME)),
+	);
+	builder
+		.maturity(MATURITY_DEFAULT)
+		.start_with(prop.transform(), prop.motion(), &prop.shape.to_shape(), flags)
+		.build(timer)
+}
+
 pub struct AgentBuilder {
 	id: Id,
 	material: Material,
 	livery: Livery,
 	gender: u8,
+	immunity: f32,
 	brain: Brain,
+	dormancy: Option<agent::Dormancy>,
 	dna: Dna,
 	state: segment::State,
 	segments: Vec<Segment>,
@@ -283,20 +329,26 @@ impl AgentBuilder {
 			livery,
 			state,
 			gender: 0u8,
+			immunity: 0.,
 			brain: Brain::default(),
+			dormancy: None,
 			dna,
 			segments: Vec::new(),
 		}
 	}
 
 	pub fn start(&mut self, transform: Transform, motion: Motion, shape: &Shape) -> &mut Self {
+		self.start_with(transform, motion, shape, segment::Flags::empty())
+	}
+
+	pub fn start_with(&mut self, transform: Transform, motion: Motion, shape: &Shape, flags: segment::Flags) -> &mut Self {
 		let segment = self.new_segment(
 			shape,
 			Winding::CW,
 			transform,
 			motion,
 			None,
-			segment::Flags::CORE | segment::Flags::STORAGE | segment::Flags::MIDDLE,
+			flags | segment::Flags::CORE | segment::Flags::STORAGE | segment::Flags::MIDDLE,
 		);
 		self.segments.clear();
 		self.segments.push(segment);
@@ -309,6 +361,26 @@ impl AgentBuilder {
 		self
 	}
 
+	#[inline]
+	pub fn immunity(&mut self, immunity: f32) -> &mut Self {
+		self.immunity = immunity;
+		self
+	}
+
+	/// Applies to the segments built so far, and to all the following ones
+	pub fn joint_strength(&mut self, joint_strength: f32) -> &mut Self {
+		self.material.joint_strength = joint_strength;
+		for segment in &mut self.segments {
+			segment.material.joint_strength = joint_strength;
+		}
+		self
+	}
+
+	pub fn dormancy(&mut self, dormancy: agent::Dormancy) -> &mut Self {
+		self.dormancy = Some(dormancy);
+		self
+	}
+
 	#[inline]
 	pub fn add(&mut self, parent_index: SegmentIndex, attachment_index_offset: isize, shape: &Shape, flags: segment::Flags)
 			   -> &mut Self {
@@ -429,6 +501,11 @@ impl AgentBuilder {
 		self
 	}
 
+	pub fn weights_touch(&mut self, weights_touch: &<Brain as TypedBrain>::WeightMatrix) -> &mut Self {
+		self.brain.weights_touch = *weights_touch;
+		self
+	}
+
 	fn new_segment(
 		&mut self, shape: &Shape, winding: Winding, transform: Transform, motion: Motion,
 		attachment: Option<segment::Attachment>, flags: segment::Flags,
@@ -439,7 +516,7 @@ impl AgentBuilder {
 			transform,
 			rest_angle,
 			motion,
-			mesh: Mesh::from_shape(shape.clone(), winding),
+			mesh: Arc::new(Mesh::from_shape(shape.clone(), winding)),
 			material: self.material.clone(),
 			livery: self.livery.clone(),
 			state: self.state.clone(),
@@ -453,7 +530,9 @@ impl AgentBuilder {
 		Agent::new(
 			self.id,
 			self.gender,
+			self.immunity,
 			&self.brain,
+			&self.dormancy,
 			&self.dna,
 			self.segments.clone().into_boxed_slice(),
 			timer,
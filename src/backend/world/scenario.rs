@@ -0,0 +1,144 @@
+use app::constants::*;
+use backend::obj;
+use backend::world::topology::Topology;
+use core::geometry::*;
+use core::geometry::Transform;
+use serde_json;
+use std::io;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum PropShape {
+	Ball { radius: f32 },
+	Box { radius: f32, ratio: f32 },
+	Poly { radius: f32, n: i8 },
+	Star {
+		radius: f32,
+		n: u8,
+		ratio1: f32,
+		ratio2: f32,
+	},
+}
+
+impl PropShape {
+	pub fn to_shape(&self) -> obj::Shape {
+		match *self {
+			PropShape::Ball { radius } => obj::Shape::new_ball(radius),
+			PropShape::Box { radius, ratio } => obj::Shape::new_box(radius, ratio),
+			PropShape::Poly { radius, n } => obj::Shape::new_poly(n, radius),
+			PropShape::Star { radius, n, ratio1, ratio2 } => obj::Shape::new_star(n, radius, ratio1, ratio2),
+		}
+	}
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Prop {
+	pub shape: PropShape,
+	pub x: f32,
+	pub y: f32,
+	#[serde(default)]
+	pub angle: f32,
+	#[serde(default)]
+	pub vx: f32,
+	#[serde(default)]
+	pub vy: f32,
+	#[serde(default)]
+	pub spin: f32,
+}
+
+impl Prop {
+	pub fn transform(&self) -> Transform { Transform::from_components(self.x, self.y, self.angle) }
+
+	pub fn motion(&self) -> Motion { Motion::from_components(self.vx, self.vy, self.spin) }
+
+	/// Props with any velocity are simulated as kinematic bodies, all others are static
+	pub fn is_kinematic(&self) -> bool { self.vx != 0. || self.vy != 0. || self.spin != 0. }
+}
+
+/// How the rigid body simulation advances, independently of the frame rate
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct PhysicsSettings {
+	/// fixed length of a physics step, in seconds
+	pub timestep: f32,
+	pub velocity_iterations: i32,
+	pub position_iterations: i32,
+	/// each fixed step is split into this many solver sub-steps
+	pub substeps: usize,
+	/// upper bound to the steps taken in a single update, the rest of the time is dropped
+	pub max_steps: usize,
+	pub diagnostics: DiagnosticsMode,
+	/// bodies moving faster than this are considered unstable
+	pub max_speed: f32,
+	pub max_spin: f32,
+}
+
+/// What to do with the energy, momentum and stability checks run after every physics update
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum DiagnosticsMode {
+	Off,
+	/// logs totals after every update at info level, and warns about agents which have blown up
+	Log,
+	/// as above, and also takes the agents which have blown up out of the simulation
+	Quarantine,
+}
+
+impl Default for DiagnosticsMode {
+	fn default() -> Self { DiagnosticsMode::Off }
+}
+
+impl Default for PhysicsSettings {
+	fn default() -> Self {
+		PhysicsSettings {
+			timestep: PHYSICS_TIMESTEP,
+			velocity_iterations: PHYSICS_VELOCITY_ITERATIONS,
+			position_iterations: PHYSICS_POSITION_ITERATIONS,
+			substeps: PHYSICS_SUBSTEPS,
+			max_steps: PHYSICS_MAX_STEPS,
+			diagnostics: DiagnosticsMode::default(),
+			max_speed: PHYSICS_MAX_SPEED,
+			max_spin: PHYSICS_MAX_SPIN,
+		}
+	}
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct Scenario {
+	#[serde(default)]
+	pub topology: Option<Topology>,
+	#[serde(default)]
+	pub physics: PhysicsSettings,
+	#[serde(default)]
+	pub props: Vec<Prop>,
+}
+
+impl Scenario {
+	pub fn parse_from_resource(data: &[u8]) -> io::Result<Self> {
+		let scenario: Scenario = serde_json::from_slice(data)?;
+		// the physics system divides by the timestep
+		let timestep = scenario.physics.timestep;
+		if !(timestep.is_finite() && timestep > 0.) {
+			return Err(io::Error::new(
+				io::ErrorKind::InvalidData,
+				format!("invalid physics timestep: {}", timestep),
+			));
+		}
+		Ok(scenario)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn timesteps_must_be_positive() {
+		for timestep in &["0", "-0.01", "1e99"] {
+			let data = format!(r#"{{"physics": {{"timestep": {}}}}}"#, timestep);
+			assert!(Scenario::parse_from_resource(data.as_bytes()).is_err(), "timestep {}", timestep);
+		}
+		let scenario = Scenario::parse_from_resource(br#"{"physics": {"timestep": 0.01}}"#).unwrap();
+		assert_eq!(0.01, scenario.physics.timestep);
+		assert!(Scenario::parse_from_resource(b"{}").is_ok());
+	}
+}
@@ -7,6 +7,7 @@ use core::geometry::*;
 use core::geometry::Transform;
 use core::clock::Seconds;
 use num::Zero;
+use std::sync::Arc;
 
 #[derive(Clone)]
 pub enum PilotRotation {
@@ -108,6 +109,8 @@ bitflags! {
 		const THRUSTER     = 0x10000u32;
 		const RUDDER       = 0x20000u32;
 		const BRAKE        = 0x40000u32;
+		const STATIC       = 0x80000u32;
+		const KINEMATIC    = 0x100000u32;
 	}
 }
 
@@ -117,7 +120,8 @@ pub struct Segment {
 	pub rest_angle: Angle,
 	pub motion: Motion,
 	pub index: SegmentIndex,
-	pub mesh: Mesh,
+	/// shared with the snapshots, meshes never change once built
+	pub mesh: Arc<Mesh>,
 	pub material: Material,
 	pub livery: Livery,
 	pub attached_to: Option<Attachment>,
@@ -95,6 +95,13 @@ impl Swarm {
 //		self.insert(entity)
 	}
 
+	pub fn spawn_with<F>(&mut self, develop: F) -> Id
+	where F: FnOnce(Id) -> Agent {
+		let id = self.next_id();
+		let entity = develop(id);
+		self.insert(entity)
+	}
+
 	pub fn rebuild(&mut self, id: Id, genome: &mut Genome, initial_state: agent::InitialState, timer: &Timer) -> Id {
 		let entity = self.phenotype.develop(genome, id, initial_state, timer);
 		self.insert(entity)
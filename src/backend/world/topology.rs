@@ -0,0 +1,178 @@
+use app::constants::*;
+use core::geometry::*;
+use std::f32::consts;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Topology {
+	Rect {
+		left: f32,
+		bottom: f32,
+		right: f32,
+		top: f32,
+	},
+	Circle { radius: f32 },
+	Polygon { vertices: Vec<[f32; 2]> },
+	Torus {
+		left: f32,
+		bottom: f32,
+		right: f32,
+		top: f32,
+	},
+}
+
+fn modulo(x: f32, m: f32) -> f32 { ((x % m) + m) % m }
+
+impl Default for Topology {
+	fn default() -> Self { Topology::from_rect(Rect::new(-WORLD_RADIUS, -WORLD_RADIUS, WORLD_RADIUS, WORLD_RADIUS)) }
+}
+
+impl Topology {
+	pub fn from_rect(extent: Rect) -> Self {
+		Topology::Rect {
+			left: extent.min.x,
+			bottom: extent.min.y,
+			right: extent.max.x,
+			top: extent.max.y,
+		}
+	}
+
+	/// Axis aligned bounding box of the world
+	pub fn extent(&self) -> Rect {
+		match *self {
+			Topology::Rect {
+				left,
+				bottom,
+				right,
+				top,
+			}
+			| Topology::Torus {
+				left,
+				bottom,
+				right,
+				top,
+			} => Rect::new(left, bottom, right, top),
+			Topology::Circle { radius } => Rect::new(-radius, -radius, radius, radius),
+			Topology::Polygon { ref vertices } => match vertices.first() {
+				None => Rect::default(),
+				Some(v0) => vertices.iter().fold(Rect::new(v0[0], v0[1], v0[0], v0[1]), |r, v| {
+					Rect::new(r.min.x.min(v[0]), r.min.y.min(v[1]), r.max.x.max(v[0]), r.max.y.max(v[1]))
+				}),
+			},
+		}
+	}
+
+	pub fn is_wrapping(&self) -> bool {
+		match *self {
+			Topology::Torus { .. } => true,
+			_ => false,
+		}
+	}
+
+	/// The closed loop enclosing the world, if it has walls
+	pub fn boundary(&self) -> Option<Box<[Position]>> {
+		match *self {
+			Topology::Rect { .. } => {
+				let extent = self.extent();
+				Some(Box::new([
+					extent.bottom_left(),
+					extent.bottom_right(),
+					extent.top_right(),
+					extent.top_left(),
+				]))
+			}
+			Topology::Circle { radius } => Some(
+				(0..WORLD_CIRCLE_SEGMENTS)
+					.map(|i| {
+						let (s, c) = (consts::PI * 2. * (i as f32 / WORLD_CIRCLE_SEGMENTS as f32)).sin_cos();
+						Position::new(c * radius, s * radius)
+					}).collect::<Vec<_>>()
+					.into_boxed_slice(),
+			),
+			Topology::Polygon { ref vertices } => Some(
+				vertices
+					.iter()
+					.map(|v| Position::new(v[0], v[1]))
+					.collect::<Vec<_>>()
+					.into_boxed_slice(),
+			),
+			Topology::Torus { .. } => None,
+		}
+	}
+
+	pub fn contains(&self, p: Position) -> bool {
+		match *self {
+			Topology::Rect { .. } => {
+				let extent = self.extent();
+				p.x >= extent.min.x && p.x <= extent.max.x && p.y >= extent.min.y && p.y <= extent.max.y
+			}
+			Topology::Circle { radius } => p.x * p.x + p.y * p.y <= radius * radius,
+			Topology::Polygon { ref vertices } => polygon_contains(&vertices[..], p),
+			Topology::Torus { .. } => true,
+		}
+	}
+
+	/// Maps a position back into the world, only has effect on wrapping topologies
+	pub fn wrap(&self, p: Position) -> Position {
+		match *self {
+			Topology::Torus { .. } => {
+				let extent = self.extent();
+				let w = extent.max.x - extent.min.x;
+				let h = extent.max.y - extent.min.y;
+				Position::new(
+					extent.min.x + modulo(p.x - extent.min.x, w),
+					extent.min.y + modulo(p.y - extent.min.y, h),
+				)
+			}
+			_ => p,
+		}
+	}
+
+	/// Shortest displacement from one position to another
+	pub fn delta(&self, from: Position, to: Position) -> Position {
+		let d = to - from;
+		match *self {
+			Topology::Torus { .. } => {
+				let extent = self.extent();
+				let w = extent.max.x - extent.min.x;
+				let h = extent.max.y - extent.min.y;
+				Position::new(d.x - w * (d.x / w).round(), d.y - h * (d.y / h).round())
+			}
+			_ => d,
+		}
+	}
+
+	/// Offsets at which an object near the edges of a wrapping world is also visible
+	pub fn ghost_offsets(&self, p: Position, margin: f32) -> Box<[Position]> {
+		let mut offsets = Vec::new();
+		if self.is_wrapping() {
+			let extent = self.extent();
+			let w = extent.max.x - extent.min.x;
+			let h = extent.max.y - extent.min.y;
+			let dx = if p.x < extent.min.x + margin {
+				w
+			} else if p.x > extent.max.x - margin {
+				-w
+			} else {
+				0.
+			};
+			let dy = if p.y < extent.min.y + margin {
+				h
+			} else if p.y > extent.max.y - margin {
+				-h
+			} else {
+				0.
+			};
+			if dx != 0. {
+				offsets.push(Position::new(dx, 0.));
+			}
+			if dy != 0. {
+				offsets.push(Position::new(0., dy));
+			}
+			if dx != 0. && dy != 0. {
+				offsets.push(Position::new(dx, dy));
+			}
+		}
+		offsets.into_boxed_slice()
+	}
+}
@@ -53,6 +53,21 @@ impl Rect {
 	pub fn top_left(&self) -> Position { Position::new(self.min.x, self.max.y) }
 }
 
+/// Even-odd rule point in polygon test
+pub fn polygon_contains<T>(vertices: &[T], p: Position) -> bool
+where T: Copy + Into<Position> {
+	let n = vertices.len();
+	let mut inside = false;
+	for i in 0..n {
+		let a: Position = vertices[i].into();
+		let b: Position = vertices[(i + n - 1) % n].into();
+		if (a.y > p.y) != (b.y > p.y) && p.x < (b.x - a.x) * (p.y - a.y) / (b.y - a.y) + a.x {
+			inside = !inside;
+		}
+	}
+	inside
+}
+
 impl Default for Rect {
 	fn default() -> Self { Rect::new(0., 0., 0., 0.) }
 }
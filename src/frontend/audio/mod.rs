@@ -142,10 +142,16 @@ impl AlertPlayer<app::Event, self::Error> for SoundSystemAlertPlayer<ThreadedSou
 			Event::SaveGenePoolToFile |
 			Event::SaveWorldToFile |
 			Event::DeselectAll |
+			Event::SaveSelectionToFile |
+			Event::FollowSelection |
 			Event::ZoomReset |
 			Event::ToggleDebug => SoundEffect::UserOption,
 
-			Event::PickMinion(_) => SoundEffect::SelectMinion,
+			Event::PickMinion(_) |
+			Event::PickArea(_, _) |
+			Event::EndLasso |
+			Event::SelectSpecies |
+			Event::SelectLineage => SoundEffect::SelectMinion,
 
 			Event::NewMinion(_) |
 			Event::RandomizeMinion(_) => SoundEffect::NewMinion,
@@ -9,6 +9,7 @@ use frontend::render::formats;
 use gfx::handle::{RenderTargetView, ShaderResourceView};
 use gfx::{CommandBuffer, Encoder, Factory, Resources};
 use std::io;
+use std::path;
 use std::vec::Drain;
 
 #[derive(Clone, Debug)]
@@ -19,11 +20,29 @@ pub struct WidgetIdGroup {
 	value_id: widget::Id,
 }
 
+#[derive(Clone, Debug)]
+pub struct SlotIdGroup {
+	label_id: widget::Id,
+	load_id: widget::Id,
+	duplicate_id: widget::Id,
+	rename_id: widget::Id,
+	delete_id: widget::Id,
+}
+
 #[derive(Clone, Debug)]
 pub struct Ids {
 	help_canvas: widget::Id,
 	help_text: widget::Id,
 
+	slot_canvas: widget::Id,
+	slot_title: widget::Id,
+	slot_name_label: widget::Id,
+	slot_name_box: widget::Id,
+	slot_rows: Vec<SlotIdGroup>,
+	slot_page_label: widget::Id,
+	slot_previous_page: widget::Id,
+	slot_next_page: widget::Id,
+
 	hud_speed_button: widget::Id,
 	hud_canvas: widget::Id,
 	hud_labels: Vec<WidgetIdGroup>,
@@ -52,6 +71,11 @@ where
 	styles: Styles,
 	ids: Ids,
 	app_events: Vec<app::Event>,
+	/// new name of the snapshots renamed in the save browser
+	slot_name: String,
+	/// snapshot whose Delete button has been clicked once, waiting for confirmation
+	pending_delete: Option<path::PathBuf>,
+	slot_actions: Vec<(path::PathBuf, app::SlotAction)>,
 	events: Vec<event::Input>,
 }
 
@@ -71,6 +95,9 @@ impl Screen {
 		styles: &Styles,
 		ids: &Ids,
 		app_events: &mut Vec<app::Event>,
+		slot_name: &mut String,
+		pending_delete: &mut Option<path::PathBuf>,
+		slot_actions: &mut Vec<(path::PathBuf, app::SlotAction)>,
 	) -> conrod::UiCell<'e>
 	{
 		let mut widgets = ui.set_widgets();
@@ -90,6 +117,115 @@ impl Screen {
 					.with_style(styles.label)
 					.set(ids.help_text, &mut widgets);
 			}
+			Screen::SaveSlots(ref save_slots) => {
+				widget::Canvas::new()
+					.pad(20.0)
+					.color(conrod::color::CHARCOAL.alpha(0.8))
+					.w_h(960.0, 160.0 + 40.0 * ids.slot_rows.len() as f64)
+					.middle_of(root_window_id)
+					.set(ids.slot_canvas, &mut widgets);
+
+				widget::Text::new("Saved worlds (F4 to close)")
+					.top_left_of(ids.slot_canvas)
+					.with_style(styles.label)
+					.set(ids.slot_title, &mut widgets);
+
+				widget::Text::new("New name")
+					.down_from(ids.slot_title, 20.0)
+					.with_style(styles.label)
+					.set(ids.slot_name_label, &mut widgets);
+
+				let edits = widget::TextBox::new(slot_name)
+					.right_from(ids.slot_name_label, 20.0)
+					.w_h(400.0, 30.0)
+					.font_size(14)
+					.set(ids.slot_name_box, &mut widgets);
+				for edit in edits {
+					if let widget::text_box::Event::Update(new_name) = edit {
+						*slot_name = new_name;
+					}
+				}
+
+				let mut previous_id = ids.slot_name_label;
+				for (save_slot, slot_ids) in save_slots.slots.iter().zip(ids.slot_rows.iter()) {
+					let description = match save_slot.contents {
+						Some(ref contents) => format!(
+							"{}    {}    population {}    extinctions {}",
+							save_slot.name,
+							save_slot.modified.format("%Y-%m-%d %H:%M:%S"),
+							contents.population,
+							contents.extinctions
+						),
+						None => format!(
+							"{}    {}    unreadable",
+							save_slot.name,
+							save_slot.modified.format("%Y-%m-%d %H:%M:%S")
+						),
+					};
+					widget::Text::new(&description)
+						.down_from(previous_id, 20.0)
+						.align_left_of(ids.slot_title)
+						.w(600.0)
+						.with_style(styles.value)
+						.set(slot_ids.label_id, &mut widgets);
+					previous_id = slot_ids.label_id;
+
+					// deleting takes a second click on the same button
+					let is_pending_delete = pending_delete.as_ref() == Some(&save_slot.path);
+					let buttons = [
+						(slot_ids.load_id, "Load", app::SlotAction::Load),
+						(slot_ids.duplicate_id, "Copy", app::SlotAction::Duplicate),
+						(slot_ids.rename_id, "Rename", app::SlotAction::Rename(slot_name.clone())),
+						(slot_ids.delete_id, if is_pending_delete { "Confirm?" } else { "Delete" }, app::SlotAction::Delete),
+					];
+					let mut left_id = slot_ids.label_id;
+					for &(button_id, label, ref action) in &buttons {
+						let pressed = widget::Button::new()
+							.label(label)
+							.right_from(left_id, 10.0)
+							.w_h(70.0, 26.0)
+							.with_style(styles.button)
+							.set(button_id, &mut widgets)
+							.was_clicked();
+						if pressed {
+							match *action {
+								app::SlotAction::Delete if !is_pending_delete => {
+									*pending_delete = Some(save_slot.path.clone());
+								}
+								_ => {
+									*pending_delete = None;
+									slot_actions.push((save_slot.path.clone(), action.clone()));
+								}
+							}
+						}
+						left_id = button_id;
+					}
+				}
+
+				widget::Text::new(&format!("Page {} of {}", save_slots.index + 1, save_slots.count))
+					.down_from(previous_id, 30.0)
+					.align_left_of(ids.slot_title)
+					.with_style(styles.label)
+					.set(ids.slot_page_label, &mut widgets);
+				let pages = [
+					(ids.slot_previous_page, "Newer", save_slots.index.checked_sub(1)),
+					(ids.slot_next_page, "Older", Some(save_slots.index + 1).filter(|&page| page < save_slots.count)),
+				];
+				let mut left_id = ids.slot_page_label;
+				for &(button_id, label, page) in &pages {
+					let pressed = widget::Button::new()
+						.label(label)
+						.right_from(left_id, 10.0)
+						.w_h(70.0, 26.0)
+						.with_style(styles.button)
+						.set(button_id, &mut widgets)
+						.was_clicked();
+					if let (true, Some(page)) = (pressed, page) {
+						app_events.push(app::Event::SaveBrowserPage(page));
+					}
+					left_id = button_id;
+				}
+			}
 			Screen::Main(ref frame_update) => {
 				let splits = ids
 					.hud_labels
@@ -227,12 +363,67 @@ impl Screen {
 					"Population",
 					&format!("{}", frame_update.simulation.population),
 				);
+				txt_with_label(
+					&mut ids_iter,
+					&mut widgets,
+					"Infected",
+					&format!("{}", frame_update.simulation.infected),
+				);
 				txt_with_label(
 					&mut ids_iter,
 					&mut widgets,
 					"Extinctions",
 					&format!("{}", frame_update.simulation.extinctions),
 				);
+				let last_save = if frame_update.is_saving {
+					"saving".to_owned()
+				} else {
+					match frame_update.since_last_save {
+						Some(elapsed) => format!("{:.0}s ago", elapsed.get()),
+						None => "never".to_owned(),
+					}
+				};
+				txt_with_label(&mut ids_iter, &mut widgets, "Last Save", &last_save);
+
+				// timeline of the states kept in memory, the present is at the right end
+				if frame_update.rewind_len > 1 {
+					let WidgetIdGroup {
+						panel_id,
+						label_id,
+						value_id,
+						panel_row_id,
+					} = ids_iter.next().unwrap().clone();
+					let last = frame_update.rewind_len - 1;
+					let position = frame_update.rewind_position.unwrap_or(last);
+
+					widget::Canvas::new()
+						.mid_left_of(panel_row_id)
+						.pad(10.0)
+						.color(conrod::color::CHARCOAL.alpha(0.4))
+						.w(300.0)
+						.h(60.0)
+						.set(panel_id, &mut widgets);
+
+					let label = match frame_update.rewind_offset {
+						Some(offset) => format!("Rewind {:.0}s", offset.get()),
+						None => "Rewind".to_owned(),
+					};
+					widget::Text::new(&label)
+						.mid_left_of(panel_id)
+						.with_style(styles.label)
+						.set(label_id, &mut widgets);
+
+					if let Some(value) = widget::Slider::new(position as f32, 0., last as f32)
+						.mid_right_of(panel_id)
+						.w_h(150.0, 20.0)
+						.set(value_id, &mut widgets)
+					{
+						let index = value.round() as usize;
+						if index != position {
+							app_events.push(app::Event::RewindTo(index));
+						}
+					}
+				}
 			}
 		};
 		widgets
@@ -280,11 +471,27 @@ where
 			font_size: Some(14),
 			..Default::default()
 		};
-		const MAX_HUD_LABELS: usize = 10;
+		const MAX_HUD_LABELS: usize = 13;
 		let ids = Ids {
 			help_canvas: ui.widget_id_generator().next(),
 			help_text: ui.widget_id_generator().next(),
 
+			slot_canvas: ui.widget_id_generator().next(),
+			slot_title: ui.widget_id_generator().next(),
+			slot_name_label: ui.widget_id_generator().next(),
+			slot_name_box: ui.widget_id_generator().next(),
+			slot_rows: (0..app::SAVE_SLOTS_PER_PAGE)
+				.map(|_| SlotIdGroup {
+					label_id: ui.widget_id_generator().next(),
+					load_id: ui.widget_id_generator().next(),
+					duplicate_id: ui.widget_id_generator().next(),
+					rename_id: ui.widget_id_generator().next(),
+					delete_id: ui.widget_id_generator().next(),
+				}).collect(),
+			slot_page_label: ui.widget_id_generator().next(),
+			slot_previous_page: ui.widget_id_generator().next(),
+			slot_next_page: ui.widget_id_generator().next(),
+
 			hud_speed_button: ui.widget_id_generator().next(),
 			hud_canvas: ui.widget_id_generator().next(),
 			hud_labels: (0..MAX_HUD_LABELS)
@@ -311,6 +518,9 @@ where
 			},
 			ids,
 			app_events: Vec::new(),
+			slot_name: String::new(),
+			pending_delete: None,
+			slot_actions: Vec::new(),
 			events: Vec::new(),
 		})
 	}
@@ -337,7 +547,16 @@ where
 		let dims = (f32::from(self.win_w), f32::from(self.win_h));
 		let window_id = self.ui.window;
 		let mut app_events = Vec::with_capacity(1);
-		let widgets = screen.draw_widgets(&mut self.ui, window_id, &self.styles, &self.ids, &mut app_events);
+		let widgets = screen.draw_widgets(
+			&mut self.ui,
+			window_id,
+			&self.styles,
+			&self.ids,
+			&mut app_events,
+			&mut self.slot_name,
+			&mut self.pending_delete,
+			&mut self.slot_actions,
+		);
 		let primitives = widgets.draw();
 		self.renderer.fill(encoder, dims, primitives, &self.image_map);
 		self.renderer.draw(self.factory, encoder, &self.image_map);
@@ -348,6 +567,8 @@ where
 
 	pub fn drain_app_events(&mut self) -> Drain<app::Event> { self.app_events.drain(..) }
 
+	pub fn drain_slot_actions(&mut self) -> Drain<(path::PathBuf, app::SlotAction)> { self.slot_actions.drain(..) }
+
 	pub fn handle_events(&mut self) {
 		for event in &self.events {
 			self.ui.handle_event(event.clone())
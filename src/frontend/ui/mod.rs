@@ -3,6 +3,8 @@ pub mod conrod_ui;
 pub mod theme;
 
 use app::FrameUpdate;
+use app::SlotPage;
+use std::sync::Arc;
 
 pub trait AlertPlayer<T, E> {
 	fn play(&mut self, alert: &T) -> Result<(), E>;
@@ -33,4 +35,5 @@ pub enum Screen {
 	#[allow(unused)]
 	Help,
 	Main(FrameUpdate),
+	SaveSlots(Arc<SlotPage>),
 }
\ No newline at end of file
@@ -1,5 +1,6 @@
 #![allow(unknown_lints)]
 #![feature(tool_lints)]
+#![cfg_attr(test, feature(test))]
 #![warn(clippy::all)]
 
 mod app;
@@ -9,6 +10,8 @@ mod frontend;
 
 #[macro_use]
 extern crate log;
+#[cfg(test)]
+extern crate test;
 extern crate chrono;
 extern crate csv;
 extern crate log4rs;
@@ -23,7 +26,10 @@ extern crate serde_derive;
 
 extern crate serde;
 extern crate serde_json;
+extern crate rmp_serde;
+extern crate flate2;
 
+#[cfg(feature = "box2d")]
 extern crate wrapped2d;
 
 #[macro_use]